@@ -1 +1,53 @@
-pub const DISCRIMINATOR:usize = 8;
\ No newline at end of file
+pub const DISCRIMINATOR:usize = 8;
+
+pub const BPS_DENOMINATOR:u64 = 10_000;
+pub const MAX_PROTOCOL_FEE_BPS:u16 = 1_000;
+pub const MAX_REFERRAL_FEE_BPS:u16 = 2_000;
+pub const MIN_CONFIG_TIMELOCK:i64 = 24 * 60 * 60;
+
+pub const MAX_MULTISIG_MEMBERS:usize = 10;
+
+pub const MAX_FLEET_UNITS:usize = 50;
+
+pub const MAX_ACCEPTED_PAYMENTS:usize = 4;
+
+pub const MAX_DEPOSIT_TIERS:usize = 4;
+
+pub const MAX_LIST_RENTERS:usize = 100;
+
+pub const MAX_PAYEES:usize = 5;
+
+pub const RENTAL_PASS_NAME:&str = "Car Rental Pass";
+pub const RENTAL_PASS_SYMBOL:&str = "RPASS";
+
+//reference priced listings are quoted in millionths of the reference currency
+pub const REFERENCE_DECIMALS:u32 = 6;
+pub const LAMPORT_DECIMALS:u8 = 9;
+
+//attestation claim bits, issuers and owners agree on region codes from CLAIM_REGION_SHIFT up
+pub const CLAIM_LICENCE_VALID:u32 = 1 << 0;
+pub const CLAIM_AGE_21_PLUS:u32 = 1 << 1;
+pub const CLAIM_AGE_25_PLUS:u32 = 1 << 2;
+pub const CLAIM_REGION_SHIFT:u32 = 8;
+
+//operator permission bits, single car listings still need the owner since the nft moves under their authority
+pub const OPERATOR_LIST:u8 = 1 << 0;
+pub const OPERATOR_UPDATE_PRICES:u8 = 1 << 1;
+pub const OPERATOR_CONFIRM_RETURNS:u8 = 1 << 2;
+pub const OPERATOR_DISPUTES:u8 = 1 << 3;
+
+//a renter who forfeits at least this share of their deposit in a dispute is banned protocol wide
+pub const BAN_DISPUTE_FORFEIT_BPS:u64 = 5_000;
+
+//how long past a rental's end the renter has before the owner can claim it as a default
+pub const OVERDUE_GRACE_PERIOD:i64 = 3 * 24 * 60 * 60;
+
+//scale of a fraction pool's reward per share, so small payouts over large share supplies still register
+pub const REWARD_PRECISION:u128 = 1_000_000_000_000;
+
+//device signed odometer readings are b"odometer" || rental_state || km (u64 le) || read_at (i64 le)
+pub const ODOMETER_READING_PREFIX:&[u8] = b"odometer";
+pub const MAX_ODOMETER_READING_AGE:i64 = 5 * 60;
+
+pub const MIN_RATING:u8 = 1;
+pub const MAX_RATING:u8 = 5;
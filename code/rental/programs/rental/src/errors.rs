@@ -37,5 +37,193 @@ pub enum ErrorCode{
     
     #[msg("NFT collection is not verified.")]
     UnverifiedCollection,
-}
 
+    #[msg("Protocol fee exceeds the allowed maximum")]
+    InvalidProtocolFee,
+
+    #[msg("Config timelock is below the allowed minimum")]
+    InvalidTimelock,
+
+    #[msg("A config change is already pending")]
+    ConfigUpdatePending,
+
+    #[msg("No pending config change")]
+    NoPendingConfig,
+
+    #[msg("Config timelock has not passed")]
+    TimelockNotElapsed,
+
+    #[msg("Arbitrator does not match config")]
+    InvalidArbitrator,
+
+    #[msg("Multisig members must be unique and within the allowed count")]
+    InvalidMembers,
+
+    #[msg("Multisig threshold must be between 1 and the member count")]
+    InvalidThreshold,
+
+    #[msg("Signer is not a multisig member")]
+    NotMultisigMember,
+
+    #[msg("Proposal already approved by this member")]
+    AlreadyApproved,
+
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Proposal has not reached the approval threshold")]
+    ThresholdNotMet,
+
+    #[msg("Proposal action does not match this instruction")]
+    ProposalActionMismatch,
+
+    #[msg("Config is not governed by this multisig")]
+    InvalidAdmin,
+
+    #[msg("Account data does not match a known layout")]
+    InvalidAccountLayout,
+
+    #[msg("Account is already on the current version")]
+    AlreadyMigrated,
+
+    #[msg("Referral fee exceeds the allowed maximum")]
+    InvalidReferralFee,
+
+    #[msg("Renter cannot refer their own rental")]
+    SelfReferral,
+
+    #[msg("Referrer accounts missing or do not match the rental")]
+    InvalidReferrer,
+
+    #[msg("Accounts for the listing's payment kind were not supplied")]
+    MissingPaymentAccounts,
+
+    #[msg("Token records and the instructions sysvar are required for programmable NFTs")]
+    MissingPnftAccounts,
+
+    #[msg("Account is not an mpl-core asset")]
+    InvalidCoreAsset,
+
+    #[msg("Core asset is not owned by the expected account")]
+    InvalidAssetOwner,
+
+    #[msg("Compressed leaf does not match the merkle tree or proof")]
+    InvalidCompressedLeaf,
+
+    #[msg("Vault custody listings need the nft vault account")]
+    MissingVault,
+
+    #[msg("Custody mode is not supported for this kind of car")]
+    UnsupportedCustody,
+
+    #[msg("Fleet already holds the maximum number of units")]
+    FleetFull,
+
+    #[msg("Car is not an available unit of this fleet")]
+    FleetUnitUnavailable,
+
+    #[msg("Fleet rentals settle through end_fleet_rental")]
+    FleetRental,
+
+    #[msg("Price feed is missing or does not match the registered source")]
+    InvalidPriceFeed,
+
+    #[msg("Price feed update is too old")]
+    StalePrice,
+
+    #[msg("Price feed confidence interval is too wide")]
+    PriceConfidenceTooWide,
+
+    #[msg("Price denomination is not supported for this kind of listing")]
+    UnsupportedDenomination,
+
+    #[msg("Payment mint is not accepted by this listing")]
+    InvalidPaymentMint,
+
+    #[msg("Listing accepts too many payment mints")]
+    TooManyPaymentMints,
+
+    #[msg("Signer is neither the owner nor the renter of this rental")]
+    NotRentalParty,
+
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+
+    #[msg("This party has already rated the rental")]
+    AlreadyRated,
+
+    #[msg("Listing has too many deposit tiers")]
+    TooManyDepositTiers,
+
+    #[msg("Deposit tier discount is above 100% or not supported for this listing")]
+    InvalidDepositTier,
+
+    #[msg("Listing requires an attestation from a registered issuer")]
+    MissingAttestation,
+
+    #[msg("Attestation issuer is not active")]
+    InactiveIssuer,
+
+    #[msg("Attestation has expired")]
+    AttestationExpired,
+
+    #[msg("Attestation does not carry the claims this listing requires")]
+    MissingClaims,
+
+    #[msg("Listing references a renter list that was not passed")]
+    MissingRenterList,
+
+    #[msg("Renter list does not match the listing")]
+    InvalidRenterList,
+
+    #[msg("Renter is not on the owner's allowlist")]
+    RenterNotAllowed,
+
+    #[msg("Renter is on the owner's blocklist")]
+    RenterBlocked,
+
+    #[msg("Renter list is full")]
+    RenterListFull,
+
+    #[msg("Renter is banned from the protocol")]
+    RenterBanned,
+
+    #[msg("Signer is neither the owner nor an operator with this permission")]
+    OperatorNotAuthorized,
+
+    #[msg("Frozen custody returns need the owner's own signature")]
+    OwnerSignatureRequired,
+
+    #[msg("Payees must be unique, at most the allowed count, with shares summing to 10000 bps")]
+    InvalidPayees,
+
+    #[msg("Payee account is missing or does not belong to the payee")]
+    InvalidPayeeAccount,
+
+    #[msg("Listing cannot be fractionalized")]
+    CannotFractionalize,
+
+    #[msg("Not enough staked shares")]
+    InsufficientShares,
+
+    #[msg("Mileage billing needs a single mint, token priced nft listing")]
+    MileageUnsupported,
+
+    #[msg("Listing bills mileage but no signed odometer reading precedes this instruction")]
+    MissingOdometerReading,
+
+    #[msg("Odometer reading is not signed by the car's device or does not match this listing")]
+    InvalidOdometerReading,
+
+    #[msg("Odometer reading is too old")]
+    StaleOdometerReading,
+
+    #[msg("Only the program upgrade authority can initialize the protocol")]
+    NotUpgradeAuthority,
+
+    #[msg("Referrer has not been approved by the protocol")]
+    ReferrerNotApproved,
+
+    #[msg("Rental is not past its end and the grace period yet")]
+    RentalNotOverdue,
+}
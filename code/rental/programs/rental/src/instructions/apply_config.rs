@@ -0,0 +1,29 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+//permissionless so a pending change cannot be held back once it is due
+#[derive(Accounts)]
+pub struct ApplyConfig<'info>{
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+    )]
+    pub config:Account<'info,Config>,
+}
+
+impl<'info> ApplyConfig<'info>{
+    pub fn apply_config(&mut self)->Result<()>{
+
+        let pending = self.config.pending.clone().ok_or(ErrorCode::NoPendingConfig)?;
+
+        let current_time = Clock::get()?;
+        require!(current_time.unix_timestamp >= pending.effective_at,ErrorCode::TimelockNotElapsed);
+
+        self.config.set_params(pending.params);
+        self.config.pending = None;
+
+        Ok(())
+    }
+}
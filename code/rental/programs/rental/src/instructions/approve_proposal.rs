@@ -0,0 +1,39 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info>{
+    #[account(
+        constraint = multisig.is_member(&member.key()) @ ErrorCode::NotMultisigMember,
+    )]
+    pub member:Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+}
+
+impl<'info> ApproveProposal<'info>{
+    pub fn approve_proposal(&mut self)->Result<()>{
+
+        require!(!self.proposal.executed,ErrorCode::ProposalAlreadyExecuted);
+        require!(!self.proposal.approvers.contains(&self.member.key()),ErrorCode::AlreadyApproved);
+
+        let multisig = &self.multisig;
+        self.proposal.approvers.retain(|approver| multisig.is_member(approver));
+        self.proposal.approvers.push(self.member.key());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct ApproveReferrer<'info>{
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Account<'info,ReferrerStats>,
+}
+
+impl<'info> ApproveReferrer<'info>{
+    pub fn approve_referrer(&mut self)->Result<()>{
+
+        let ProposalAction::ApproveReferrer { referrer, approved } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(referrer == self.referrer_stats.referrer,ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        self.referrer_stats.approved = approved;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct BanWallet<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    /// CHECK: banned wallet, matched against the proposal
+    pub wallet:UncheckedAccount<'info>,
+
+    //overwrites an automatic ban with the admin's reason
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = DISCRIMINATOR + BanEntry::INIT_SPACE,
+        seeds = [b"ban", wallet.key().as_ref()],
+        bump
+    )]
+    pub ban_entry:Account<'info,BanEntry>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> BanWallet<'info>{
+    pub fn ban_wallet(&mut self,bumps:BanWalletBumps)->Result<()>{
+
+        let ProposalAction::BanWallet { wallet, code } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(wallet == self.wallet.key(),ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        self.ban_entry.set_inner(BanEntry{
+            version:BanEntry::VERSION,
+            wallet,
+            reason:BanReason::Manual{code},
+            banned_at:Clock::get()?.unix_timestamp,
+            ban_bump:bumps.ban_entry,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct CancelConfig<'info>{
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+}
+
+impl<'info> CancelConfig<'info>{
+    pub fn cancel_config(&mut self)->Result<()>{
+
+        require!(self.proposal.action == ProposalAction::CancelConfig,ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        require!(self.config.pending.is_some(),ErrorCode::NoPendingConfig);
+        self.config.pending = None;
+
+        Ok(())
+    }
+}
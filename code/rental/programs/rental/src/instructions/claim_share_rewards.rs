@@ -0,0 +1,61 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ClaimShareRewards<'info>{
+    #[account(mut)]
+    pub holder:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fraction", fraction_pool.rental_state.as_ref()],
+        bump = fraction_pool.pool_bump,
+    )]
+    pub fraction_pool:Box<Account<'info,FractionPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"share_position", fraction_pool.key().as_ref(), holder.key().as_ref()],
+        bump = position.position_bump,
+        has_one = holder,
+    )]
+    pub position:Box<Account<'info,SharePosition>>,
+
+    #[account(address = fraction_pool.reward_mint)]
+    pub reward_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_rewards:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+}
+
+impl<'info> ClaimShareRewards<'info>{
+
+    pub fn claim_share_rewards(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        let pending = self.fraction_pool.settle(&mut self.position,self.reward_vault.amount)?;
+        FractionPool::pay_rewards(&self.fraction_pool,self.token_program.to_account_info(),self.reward_vault.to_account_info(),&self.reward_mint,self.holder_rewards.to_account_info(),pending,remaining_accounts)
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, constants::*, instructions::ListingOptions};
+
+#[derive(Accounts)]
+pub struct CreateFleet<'info>{
+    pub owner:SystemAccount<'info>,
+
+    //the owner, or an operator they authorised to set up fleets
+    #[account(mut)]
+    pub authority:Signer<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Box<Account<'info,OperatorGrant>>>,
+
+    pub collection_mint:InterfaceAccount<'info,Mint>,
+
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = DISCRIMINATOR + FleetPool::INIT_SPACE,
+        seeds = [b"fleet", owner.key().as_ref(), collection_mint.key().as_ref()],
+        bump
+    )]
+    pub fleet:Account<'info,FleetPool>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_fee_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+}
+
+impl<'info> CreateFleet<'info>{
+    pub fn create_fleet(&mut self,rent_fee:u64,deposit_amount:u64,options:ListingOptions,bumps:CreateFleetBumps)->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref().map(|grant| &**grant),OPERATOR_LIST)?;
+
+        //units stay in the fleet's own vaults
+        require!(options.custody == CustodyMode::Vault,ErrorCode::UnsupportedCustody);
+        require!(options.denomination == PriceDenomination::Token,ErrorCode::UnsupportedDenomination);
+        require!(options.accepted_payments.is_empty(),ErrorCode::InvalidPaymentMint);
+        require!(options.deposit_tiers.is_empty(),ErrorCode::InvalidDepositTier);
+        require!(options.payees.is_empty(),ErrorCode::InvalidPayees);
+
+        options.validate()?;
+
+        //units can only ever be paid in the mint the fleet is created with
+        let rent_fee_mint = match options.payment_kind {
+            PaymentKind::Token => {
+                require!(self.rent_fee_mint.is_some() && self.owner_fee_ata.is_some(),ErrorCode::MissingPaymentAccounts);
+                self.rent_fee_mint.as_ref().map(|mint| mint.key())
+            },
+            PaymentKind::Lamports => None,
+        };
+
+        self.fleet.set_inner(FleetPool{
+            version:FleetPool::VERSION,
+            owner:self.owner.key(),
+            collection_mint:self.collection_mint.key(),
+            rent_fee,
+            deposit_amount,
+            referral_fee_bps:options.referral_fee_bps,
+            transfer_fee_payer:options.transfer_fee_payer,
+            payment_kind:options.payment_kind,
+            rent_fee_mint,
+            rented_units:0,
+            available:Vec::new(),
+            required_claims:options.required_claims,
+            allowlist:options.allowlist,
+            blocklist:options.blocklist,
+            fleet_bump:bumps.fleet,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info>{
+    #[account(
+        mut,
+        constraint = multisig.is_member(&proposer.key()) @ ErrorCode::NotMultisigMember,
+    )]
+    pub proposer:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = DISCRIMINATOR + Proposal::INIT_SPACE,
+        seeds = [b"proposal", multisig.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> CreateProposal<'info>{
+    pub fn create_proposal(&mut self,action:ProposalAction,bumps:CreateProposalBumps)->Result<()>{
+
+        if let ProposalAction::UpdateMultisig { members, threshold } = &action {
+            Multisig::validate_members(members,*threshold)?;
+        }
+
+        //creating a proposal counts as the proposer's approval
+        self.proposal.set_inner(Proposal{
+            version:Proposal::VERSION,
+            multisig:self.multisig.key(),
+            proposer:self.proposer.key(),
+            index:self.multisig.proposal_count,
+            action,
+            approvers:vec![self.proposer.key()],
+            executed:false,
+            proposal_bump:bumps.proposal,
+        });
+
+        self.multisig.proposal_count = self.multisig.proposal_count.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+
+        Ok(())
+    }
+}
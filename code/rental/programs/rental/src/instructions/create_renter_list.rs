@@ -0,0 +1,37 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*, constants::*};
+
+#[derive(Accounts)]
+#[instruction(list_id:u16)]
+pub struct CreateRenterList<'info>{
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DISCRIMINATOR + RenterList::INIT_SPACE,
+        seeds = [b"renter_list", owner.key().as_ref(), list_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub renter_list:Account<'info,RenterList>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> CreateRenterList<'info>{
+    pub fn create_renter_list(&mut self,list_id:u16,kind:RenterListKind,bumps:CreateRenterListBumps)->Result<()>{
+
+        self.renter_list.set_inner(RenterList{
+            version:RenterList::VERSION,
+            owner:self.owner.key(),
+            list_id,
+            kind,
+            renters:Vec::new(),
+            list_bump:bumps.renter_list,
+        });
+
+        Ok(())
+    }
+}
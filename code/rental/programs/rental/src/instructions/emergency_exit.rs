@@ -1,28 +1,55 @@
-use anchor_lang::{prelude::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken, metadata::{MasterEditionAccount, Metadata, MetadataAccount}, 
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken}, metadata::{MasterEditionAccount, Metadata, MetadataAccount}, 
+    token_2022::Token2022, token_interface::{Mint, TokenAccount, TokenInterface}
 };
 
-use crate::{state::*,errors::ErrorCode, constants::*};
+use crate::{state::*,errors::ErrorCode, constants::*, utils::{split_payee_accounts, Ban, CarNft, Escrow, NftHolder, PnftAccounts, RentalPass, Settlement}};
 
 #[derive(Accounts)]
 pub struct EmergencyExit<'info> {
 
     #[account(mut)]
-    pub owner:Signer<'info>,
+    pub owner:SystemAccount<'info>,
 
+    //the owner, or an operator they authorised to settle disputes
     #[account(mut)]
-    pub renter:Signer<'info>,
+    pub authority:Signer<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Box<Account<'info,OperatorGrant>>>,
+
+    //rulings don't need the renter, who may well be the one not responding
+    #[account(mut)]
+    pub renter:SystemAccount<'info>,
 
 
     #[account(mut)]
     pub arbitrator:Signer<'info>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.arbitrator == arbitrator.key() @ ErrorCode::InvalidArbitrator,
+    )]
+    pub config:Account<'info,Config>,
+
      pub collection_mint:InterfaceAccount<'info,Mint>,
 
+    //the fleet a fleet unit's rental came out of, it goes back into the pool
+    #[account(
+        mut,
+        seeds = [b"fleet", owner.key().as_ref(), fleet.collection_mint.as_ref()],
+        bump = fleet.fleet_bump,
+        has_one = owner,
+    )]
+    pub fleet:Option<Box<Account<'info,FleetPool>>>,
+
     pub car_nft_mint:InterfaceAccount<'info,Mint>,
-    pub rent_fee_mint:InterfaceAccount<'info,Mint>,
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
 
 
     #[account(
@@ -38,16 +65,24 @@ pub struct EmergencyExit<'info> {
         associated_token::mint = rent_fee_mint,
         associated_token::authority = rental_state,
     )]
-    pub rent_vault:InterfaceAccount<'info,TokenAccount>,
+    pub rent_vault:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
 
     #[account(
         mut,
         associated_token::mint = car_nft_mint,
         associated_token::authority = rental_state,
     )]
-    pub vault:InterfaceAccount<'info,TokenAccount>,
+    pub vault:Option<InterfaceAccount<'info,TokenAccount>>,
 
     #[account(
+        mut,
         seeds=[
             b"metadata",
             metadata_program.key().as_ref(),
@@ -56,7 +91,7 @@ pub struct EmergencyExit<'info> {
         seeds::program = metadata_program.key(),
         bump,
         constraint = metadata.collection.as_ref().unwrap().key.as_ref() == collection_mint.key().as_ref(),
-        constraint = metadata.collection.as_ref().unwrap().verified == true,
+        constraint = metadata.collection.as_ref().unwrap().verified,
     )]
     pub metadata:Account<'info,MetadataAccount>,
 
@@ -72,13 +107,36 @@ pub struct EmergencyExit<'info> {
     )]
     pub master_edition:Account<'info,MasterEditionAccount>,
 
+    /// CHECK: token-metadata token record of the owner's nft account, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", owner_ata.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub owner_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token-metadata token record of the vault, derivation checked by token-metadata
+    #[account(mut)]
+    pub vault_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: rule set of a programmable nft, checked against the metadata by token-metadata
+    pub authorization_rules:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token auth rules program, checked by token-metadata
+    pub authorization_rules_program:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
+
 
     #[account(
         mut,
         associated_token::mint = rent_fee_mint,
         associated_token::authority = renter,
     )]
-    pub renter_ata:InterfaceAccount<'info,TokenAccount>,
+    pub renter_ata:Option<InterfaceAccount<'info,TokenAccount>>,
 
           #[account(
         mut,
@@ -87,94 +145,209 @@ pub struct EmergencyExit<'info> {
     )]
     pub owner_ata:InterfaceAccount<'info,TokenAccount>,
 
+    //the renter may have paid in an accepted mint the owner holds no account for yet
       #[account(
-        mut,
+        init_if_needed,
+        payer = authority,
         associated_token::mint = rent_fee_mint,
         associated_token::authority = owner,
     )]
-    pub owner_fee_ata:InterfaceAccount<'info,TokenAccount>,
+    pub owner_fee_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+
+    //track records of both sides, created by the authority the first time
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_profile:Box<Account<'info,UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", renter.key().as_ref()],
+        bump
+    )]
+    pub renter_profile:Box<Account<'info,UserProfile>>,
 
+    //the referrer the rental was made under, its referral is closed out unpaid
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Box<Account<'info,ReferrerStats>>>,
+
+    /// CHECK: renter's protocol ban entry, created if the ruling goes heavily against them
+    #[account(
+        mut,
+        seeds = [b"ban", renter.key().as_ref()],
+        bump
+    )]
+    pub ban_entry:UncheckedAccount<'info>,
+
+    /// CHECK: renter's rental pass mint, still empty for rentals started before passes were issued
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:UncheckedAccount<'info>,
+
+    /// CHECK: renter's rental pass account
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&renter.key(), &rental_pass_mint.key(), &token_2022_program.key()),
+    )]
+    pub renter_pass:UncheckedAccount<'info>,
 
     pub system_program:Program<'info,System>,
     pub associated_token_program:Program<'info,AssociatedToken>,
     pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
     pub metadata_program:Program<'info,Metadata>
 }
 
 impl<'info> EmergencyExit<'info>{
 
-    pub fn exit_payout(&mut self, renter_payout:u64,owner_payout:u64)->Result<()>{
-        self.rental_state.status = StatusData::Dispute;
-         require!(self.rental_state.status == StatusData::Dispute, ErrorCode::DisputeNotInitiated);
-
-    let total_in_escrow = self.rental_state.rent_fee.checked_add(self.rental_state.deposit_amount).unwrap();
-    require!(renter_payout.checked_add(owner_payout).unwrap() <= total_in_escrow, ErrorCode::InvalidPayout);
-
-        //sending nft to owner
-           self.transfer_generic(
-        1,
-        self.car_nft_mint.to_account_info(),
-        self.owner_ata.to_account_info(),      
-        self.vault.to_account_info(),
-        self.car_nft_mint.decimals,
-        self.rental_state.to_account_info()
-    )?;
-
-         if owner_payout > 0 {
-        self.transfer_generic(
-            owner_payout,
-            self.rent_fee_mint.to_account_info(),
-            self.owner_fee_ata.to_account_info(), 
-            self.rent_vault.to_account_info(),   
-            self.rent_fee_mint.decimals,
-            self.rental_state.to_account_info()
+    pub fn exit_payout(&mut self,bumps:EmergencyExitBumps,renter_payout:u64,owner_payout:u64,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref().map(|grant| &**grant),OPERATOR_DISPUTES)?;
+
+        //the ruling's ban and track record land on the rental's own renter
+        require!(self.rental_state.rented && self.rental_state.renter == Some(self.renter.key()),ErrorCode::InvalidRenter);
+
+        //owner side payees first, then transfer hook accounts
+        let (payee_accounts,remaining_accounts) = split_payee_accounts(&self.rental_state,remaining_accounts)?;
+
+        //revoking a legacy nft's delegate on the owner's account takes their signature
+        if self.rental_state.custody == CustodyMode::Frozen {
+            require!(self.owner.is_signer,ErrorCode::OwnerSignatureRequired);
+        }
+
+        match self.rental_state.fleet {
+            //fleet units never leave the fleet vault, they just become rentable again
+            Some(fleet) => {
+                let pool = self.fleet.as_mut().ok_or(ErrorCode::FleetRental)?;
+                require_keys_eq!(pool.key(),fleet,ErrorCode::FleetRental);
+                pool.available.push(self.rental_state.car_nft_mint);
+                pool.rented_units = pool.rented_units.checked_sub(1).ok_or(ErrorCode::ValueOverflow)?;
+            },
+            //fractionalized cars stay locked in the vault for the next renter
+            None if self.rental_state.fraction_pool.is_some() => {},
+            None => self.return_nft(remaining_accounts)?,
+        }
+
+        let escrow = Escrow::for_listing(
+            &self.rental_state,
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info(),
+            self.rent_fee_mint.as_ref(),
+            self.rent_vault.as_ref().map(|vault| vault.to_account_info()),
+            self.sol_vault.as_ref().map(|vault| vault.to_account_info()),
+            remaining_accounts,
         )?;
-    }
-       
-        if renter_payout > 0 {
-        self.transfer_generic(
+
+        let mut settlement = Settlement{
+            owner_payee:escrow.payee(self.owner_fee_ata.as_ref().map(|ata| ata.to_account_info()),self.owner.to_account_info())?,
+            renter_payee:escrow.payee(self.renter_ata.as_ref().map(|ata| ata.to_account_info()),self.renter.to_account_info())?,
+            pass:RentalPass::new(
+                self.token_2022_program.to_account_info(),
+                self.rental_pass_mint.to_account_info(),
+                self.renter_pass.to_account_info(),
+                self.renter.to_account_info(),
+                self.rental_state.to_account_info(),
+                self.system_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            ),
+            escrow,
+            payee_accounts,
+            owner:self.owner.to_account_info(),
+            rental_state:&mut self.rental_state,
+            owner_profile:&mut self.owner_profile,
+            owner_profile_bump:bumps.owner_profile,
+            renter_profile:&mut self.renter_profile,
+            renter_profile_bump:bumps.renter_profile,
+        };
+
+        let ban = Ban{
+            ban_entry:self.ban_entry.to_account_info(),
+            payer:self.arbitrator.to_account_info(),
+            system_program:self.system_program.to_account_info(),
+        };
+
+        settlement.dispute(
+            self.arbitrator.key(),
             renter_payout,
-            self.rent_fee_mint.to_account_info(),
-            self.renter_ata.to_account_info(),   
-            self.rent_vault.to_account_info(),  
-            self.rent_fee_mint.decimals,
-            self.rental_state.to_account_info()
+            owner_payout,
+            ban,
+            bumps.ban_entry,
+            self.referrer_stats.as_deref_mut().map(|referrer_stats| &mut **referrer_stats),
         )?;
-    }
-        self.rental_state.dispute_caller  =Some( self.arbitrator.key());
-        self.rental_state.rental_duration = None;
-        self.rental_state.renter = None;
-        self.rental_state.rented = false;
-        self.rental_state.status = StatusData::Finished;
+
+        //the renter funded a fleet unit's rental_state, the next rental creates a fresh one
+        if self.rental_state.fleet.is_some() {
+            return self.rental_state.close(self.renter.to_account_info());
+        }
+
+        //fractionalized listings go straight back on offer
+        if self.rental_state.fraction_pool.is_some() {
+            self.rental_state.rental_start_time = None;
+            self.rental_state.dispute_caller = None;
+            self.rental_state.payment_mint = None;
+            self.rental_state.status = StatusData::Active;
+        }
+
         Ok(())
-    
     }
 
-    pub fn transfer_generic(&mut self, amount:u64, mint:AccountInfo<'info>, to:AccountInfo<'info>, from:AccountInfo<'info>,decimals:u8,authority:AccountInfo<'info>)->Result<()>{
-        let cpi_program = self.token_program.to_account_info();
+    pub fn car_nft<'a>(&self,remaining_accounts:&'a [AccountInfo<'info>])->CarNft<'a,'info>{
+        CarNft::new(
+            self.token_program.to_account_info(),
+            &self.car_nft_mint,
+            &self.metadata,
+            self.master_edition.to_account_info(),
+            self.authority.to_account_info(),
+            PnftAccounts{
+                metadata_program:self.metadata_program.to_account_info(),
+                system_program:self.system_program.to_account_info(),
+                associated_token_program:self.associated_token_program.to_account_info(),
+                sysvar_instructions:self.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+                authorization_rules_program:self.authorization_rules_program.as_ref().map(|account| account.to_account_info()),
+                authorization_rules:self.authorization_rules.as_ref().map(|account| account.to_account_info()),
+            },
+            remaining_accounts,
+        )
+    }
 
-        let cpi_accounts = TransferChecked{
-            from:from.to_account_info(),
-            to:to.to_account_info(),
-            mint:mint.to_account_info(),
-            authority:authority.to_account_info()
-        };
+    pub fn return_nft(&self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
-        let car_nft_mint = self.car_nft_mint.key();
-        let owner = self.owner.key();
-        let seeds  =[
-            b"rental",
-            car_nft_mint.as_ref(),
-            owner.as_ref(),
-            &[self.rental_state.rental_bump]
-        ];
+        let owner_holder = NftHolder{
+            token:self.owner_ata.to_account_info(),
+            owner:self.owner.to_account_info(),
+            token_record:self.owner_token_record.as_ref().map(|account| account.to_account_info()),
+        };
 
-        let signer_seed = &[&seeds[..]];
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
 
-        let ctx = CpiContext::new_with_signer(cpi_program,cpi_accounts,signer_seed);
+        match self.rental_state.custody {
+            CustodyMode::Vault => {
+                let vault = self.vault.as_ref().ok_or(ErrorCode::MissingVault)?;
 
-        transfer_checked(ctx, amount,decimals)?;
+                let vault_holder = NftHolder{
+                    token:vault.to_account_info(),
+                    owner:self.rental_state.to_account_info(),
+                    token_record:self.vault_token_record.as_ref().map(|account| account.to_account_info()),
+                };
 
-        Ok(())
+                self.car_nft(remaining_accounts).transfer(vault_holder,owner_holder,&[&seeds[..]])
+            },
+            CustodyMode::Frozen => self.car_nft(remaining_accounts).unlock(owner_holder,self.rental_state.to_account_info(),&[&seeds[..]]),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,262 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_2022::Token2022, token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, constants::*, utils::{split_payee_accounts, CompressedCar, CompressedLeaf, Escrow, Fees, RentalPass, Settlement, BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID}};
+
+#[derive(Accounts)]
+#[instruction(leaf:CompressedLeaf)]
+pub struct EndCompressedRental<'info>{
+    #[account(mut)]
+    pub owner:SystemAccount<'info>,
+
+    //the owner, or an operator they authorised to confirm returns
+    #[account(mut)]
+    pub authority:Signer<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Box<Account<'info,OperatorGrant>>>,
+
+    #[account(mut)]
+    pub renter:Signer<'info>,
+
+    pub collection_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    pub rent_fee_mint:Option<Box<InterfaceAccount<'info,Mint>>>,
+
+    #[account(
+        mut,
+        seeds=[b"rental", leaf.asset_id.as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one=owner,
+        close=owner
+    )]
+    pub rental_state:Box<Account<'info,RentalState>>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+    )]
+    pub config:Box<Account<'info,Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = config,
+    )]
+    pub treasury:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Box<Account<'info,ReferrerStats>>>,
+
+    #[account(
+        mut,
+        token::mint = rent_fee_mint,
+    )]
+    pub referrer_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(mut)]
+    pub referrer:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = rental_state,
+    )]
+    pub rent_vault:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = renter,
+    )]
+    pub renter_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    //the renter may have paid in an accepted mint the owner holds no account for yet
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    /// CHECK: bubblegum tree config
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum_program.key(),
+        bump
+    )]
+    pub tree_config:UncheckedAccount<'info>,
+
+    /// CHECK: concurrent merkle tree holding the car, the leaf is proven by bubblegum
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree:UncheckedAccount<'info>,
+
+    /// CHECK: spl noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper:UncheckedAccount<'info>,
+
+    /// CHECK: spl account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program:UncheckedAccount<'info>,
+
+    /// CHECK: bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program:UncheckedAccount<'info>,
+
+    //track records of both sides, each pays for their own profile the first time
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_profile:Box<Account<'info,UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", renter.key().as_ref()],
+        bump
+    )]
+    pub renter_profile:Box<Account<'info,UserProfile>>,
+
+    //proof of this completed rental, lets each side rate the other once
+    #[account(
+        init,
+        payer = authority,
+        space = DISCRIMINATOR + RentalReceipt::INIT_SPACE,
+        seeds = [b"receipt", rental_state.key().as_ref(), rental_state.rental_start_time.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt:Box<Account<'info,RentalReceipt>>,
+
+    /// CHECK: renter's rental pass mint, still empty for rentals started before passes were issued
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:UncheckedAccount<'info>,
+
+    /// CHECK: renter's rental pass account
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&renter.key(), &rental_pass_mint.key(), &token_2022_program.key()),
+    )]
+    pub renter_pass:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
+}
+
+impl<'info> EndCompressedRental<'info>{
+
+    pub fn end_compressed_rental(&mut self,bumps:EndCompressedRentalBumps,leaf:CompressedLeaf,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref().map(|grant| &**grant),OPERATOR_CONFIRM_RETURNS)?;
+
+        let current_time = Clock::get()?;
+
+        require!(self.rental_state.renter == Some(self.renter.key()),ErrorCode::InvalidRenter);
+        require!(current_time.unix_timestamp >= self.rental_state.rental_start_time.ok_or(ErrorCode::CarNotListed)?,ErrorCode::RentalPeriodNotEnd);
+
+        let (proof,remaining_accounts) = leaf.split_proof(remaining_accounts)?;
+        let (payee_accounts,remaining_accounts) = split_payee_accounts(&self.rental_state,remaining_accounts)?;
+
+        //sending car to owner
+        self.return_car(&leaf,proof)?;
+
+        let escrow = Escrow::for_listing(
+            &self.rental_state,
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info(),
+            self.rent_fee_mint.as_deref(),
+            self.rent_vault.as_ref().map(|vault| vault.to_account_info()),
+            self.sol_vault.as_ref().map(|vault| vault.to_account_info()),
+            remaining_accounts,
+        )?;
+
+        let treasury = escrow.payee(self.treasury.as_ref().map(|ata| ata.to_account_info()),self.config.to_account_info())?;
+
+        let mut settlement = Settlement{
+            owner_payee:escrow.payee(self.owner_fee_ata.as_ref().map(|ata| ata.to_account_info()),self.owner.to_account_info())?,
+            renter_payee:escrow.payee(self.renter_ata.as_ref().map(|ata| ata.to_account_info()),self.renter.to_account_info())?,
+            pass:RentalPass::new(
+                self.token_2022_program.to_account_info(),
+                self.rental_pass_mint.to_account_info(),
+                self.renter_pass.to_account_info(),
+                self.renter.to_account_info(),
+                self.rental_state.to_account_info(),
+                self.system_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            ),
+            escrow,
+            payee_accounts,
+            owner:self.owner.to_account_info(),
+            rental_state:&mut self.rental_state,
+            owner_profile:&mut self.owner_profile,
+            owner_profile_bump:bumps.owner_profile,
+            renter_profile:&mut self.renter_profile,
+            renter_profile_bump:bumps.renter_profile,
+        };
+
+        //the listing closes here, so the lamport vault's rent reserve goes back to the owner
+        settlement.complete(
+            Fees{
+                config:&self.config,
+                treasury,
+                referrer_stats:self.referrer_stats.as_deref_mut().map(|referrer_stats| &mut **referrer_stats),
+                referrer_fee_ata:self.referrer_fee_ata.as_deref(),
+                referrer_wallet:self.referrer.as_ref().map(|referrer| referrer.to_account_info()),
+            },
+            &mut self.receipt,
+            bumps.receipt,
+            0,
+            self.owner.to_account_info(),
+        )
+    }
+
+    pub fn return_car(&self,leaf:&CompressedLeaf,proof:&[AccountInfo<'info>])->Result<()>{
+
+        leaf.verify_asset_id(&self.merkle_tree.key())?;
+        leaf.verify_collection(&self.collection_mint.key())?;
+
+        let compressed_car = CompressedCar{
+            bubblegum_program:self.bubblegum_program.to_account_info(),
+            tree_config:self.tree_config.to_account_info(),
+            merkle_tree:self.merkle_tree.to_account_info(),
+            log_wrapper:self.log_wrapper.to_account_info(),
+            compression_program:self.compression_program.to_account_info(),
+            system_program:self.system_program.to_account_info(),
+        };
+
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        compressed_car.transfer(leaf,proof,self.rental_state.to_account_info(),self.owner.to_account_info(),&[&seeds[..]])
+    }
+}
@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_2022::Token2022, token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, constants::*, utils::{Escrow, Fees, RentalPass, Settlement}};
+
+#[derive(Accounts)]
+pub struct EndFleetRental<'info>{
+    #[account(mut)]
+    pub owner:SystemAccount<'info>,
+
+    //the owner, or an operator they authorised to confirm returns
+    #[account(mut)]
+    pub authority:Signer<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Box<Account<'info,OperatorGrant>>>,
+
+    #[account(mut)]
+    pub renter:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fleet", owner.key().as_ref(), fleet.collection_mint.as_ref()],
+        bump = fleet.fleet_bump,
+        has_one = owner,
+        constraint = fleet.rent_fee_mint == rent_fee_mint.as_ref().map(|mint| mint.key()) @ ErrorCode::InvalidPaymentMint,
+    )]
+    pub fleet:Box<Account<'info,FleetPool>>,
+
+    /// CHECK: the rented unit, pinned by the rental_state seeds
+    pub car_nft_mint:UncheckedAccount<'info>,
+    pub rent_fee_mint:Option<Box<InterfaceAccount<'info,Mint>>>,
+
+    //the renter funded the unit's rental_state, so it closes back to them
+    #[account(
+        mut,
+        seeds=[b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one=owner,
+        constraint = rental_state.fleet == Some(fleet.key()) @ ErrorCode::FleetRental,
+        close=renter
+    )]
+    pub rental_state:Box<Account<'info,RentalState>>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+    )]
+    pub config:Box<Account<'info,Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = config,
+    )]
+    pub treasury:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Box<Account<'info,ReferrerStats>>>,
+
+    #[account(
+        mut,
+        token::mint = rent_fee_mint,
+    )]
+    pub referrer_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(mut)]
+    pub referrer:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = rental_state,
+    )]
+    pub rent_vault:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = renter,
+    )]
+    pub renter_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    //track records of both sides, each pays for their own profile the first time
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_profile:Box<Account<'info,UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", renter.key().as_ref()],
+        bump
+    )]
+    pub renter_profile:Box<Account<'info,UserProfile>>,
+
+    //proof of this completed rental, lets each side rate the other once
+    #[account(
+        init,
+        payer = authority,
+        space = DISCRIMINATOR + RentalReceipt::INIT_SPACE,
+        seeds = [b"receipt", rental_state.key().as_ref(), rental_state.rental_start_time.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt:Box<Account<'info,RentalReceipt>>,
+
+    /// CHECK: renter's rental pass mint, still empty for rentals started before passes were issued
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:UncheckedAccount<'info>,
+
+    /// CHECK: renter's rental pass account
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&renter.key(), &rental_pass_mint.key(), &token_2022_program.key()),
+    )]
+    pub renter_pass:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
+}
+
+impl<'info> EndFleetRental<'info>{
+
+    pub fn end_fleet_rental(&mut self,bumps:EndFleetRentalBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref().map(|grant| &**grant),OPERATOR_CONFIRM_RETURNS)?;
+
+        let current_time = Clock::get()?;
+
+        if self.rental_state.renter!=Some(self.renter.key())
+        {return Err(ErrorCode::InvalidRenter.into())}
+
+        require!(current_time.unix_timestamp >= self.rental_state.rental_start_time.unwrap(),ErrorCode::RentalPeriodNotEnd);
+
+        //the unit never left the fleet vault, it just becomes rentable again
+        self.fleet.available.push(self.car_nft_mint.key());
+        self.fleet.rented_units = self.fleet.rented_units.checked_sub(1).ok_or(ErrorCode::ValueOverflow)?;
+
+        let escrow = Escrow::for_listing(
+            &self.rental_state,
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info(),
+            self.rent_fee_mint.as_deref(),
+            self.rent_vault.as_ref().map(|vault| vault.to_account_info()),
+            self.sol_vault.as_ref().map(|vault| vault.to_account_info()),
+            remaining_accounts,
+        )?;
+
+        let treasury = escrow.payee(self.treasury.as_ref().map(|ata| ata.to_account_info()),self.config.to_account_info())?;
+
+        let mut settlement = Settlement{
+            owner_payee:escrow.payee(self.owner_fee_ata.as_ref().map(|ata| ata.to_account_info()),self.owner.to_account_info())?,
+            renter_payee:escrow.payee(self.renter_ata.as_ref().map(|ata| ata.to_account_info()),self.renter.to_account_info())?,
+            pass:RentalPass::new(
+                self.token_2022_program.to_account_info(),
+                self.rental_pass_mint.to_account_info(),
+                self.renter_pass.to_account_info(),
+                self.renter.to_account_info(),
+                self.rental_state.to_account_info(),
+                self.system_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            ),
+            escrow,
+            payee_accounts:&[],
+            owner:self.owner.to_account_info(),
+            rental_state:&mut self.rental_state,
+            owner_profile:&mut self.owner_profile,
+            owner_profile_bump:bumps.owner_profile,
+            renter_profile:&mut self.renter_profile,
+            renter_profile_bump:bumps.renter_profile,
+        };
+
+        //the renter fronted the lamport vault's rent reserve, so it goes back to them
+        settlement.complete(
+            Fees{
+                config:&self.config,
+                treasury,
+                referrer_stats:self.referrer_stats.as_deref_mut().map(|referrer_stats| &mut **referrer_stats),
+                referrer_fee_ata:self.referrer_fee_ata.as_deref(),
+                referrer_wallet:self.referrer.as_ref().map(|referrer| referrer.to_account_info()),
+            },
+            &mut self.receipt,
+            bumps.receipt,
+            0,
+            self.renter.to_account_info(),
+        )
+    }
+}
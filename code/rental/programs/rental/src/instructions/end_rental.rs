@@ -1,16 +1,26 @@
-use anchor_lang::{prelude::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken, metadata::{MasterEditionAccount, Metadata, MetadataAccount},
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken}, metadata::{MasterEditionAccount, Metadata, MetadataAccount},
+    token_2022::Token2022, token_interface::{Mint, TokenAccount, TokenInterface}
 };
 
-use crate::{state::*,errors::ErrorCode, constants::*};
+use crate::{state::*,errors::ErrorCode, constants::*, utils::{split_payee_accounts, CarNft, Escrow, Fees, NftHolder, OdometerReading, PnftAccounts, RentalPass, Settlement}};
 
 
 #[derive(Accounts)]
 pub struct EndRental<'info>{
     #[account(mut)]
-    pub owner:Signer<'info>,
+    pub owner:SystemAccount<'info>,
+
+    //the owner, or an operator they authorised to confirm returns
+    #[account(mut)]
+    pub authority:Signer<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Box<Account<'info,OperatorGrant>>>,
 
     #[account(mut)]
     pub renter:Signer<'info>,
@@ -18,32 +28,71 @@ pub struct EndRental<'info>{
     pub collection_mint:Box<InterfaceAccount<'info,Mint>>,
 
     pub car_nft_mint:Box<InterfaceAccount<'info,Mint>>,
-    pub rent_fee_mint:Box<InterfaceAccount<'info,Mint>>,
+    pub rent_fee_mint:Option<Box<InterfaceAccount<'info,Mint>>>,
 
       #[account(
         mut,
         seeds=[b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
         bump = rental_state.rental_bump,
         has_one=owner,
-        close=owner
     )]
     pub rental_state:Box<Account<'info,RentalState>>,
 
+    //lamport protocol fees accrue on the config account itself
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+    )]
+    pub config:Box<Account<'info,Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = config,
+    )]
+    pub treasury:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Box<Account<'info,ReferrerStats>>>,
+
+    #[account(
+        mut,
+        token::mint = rent_fee_mint,
+    )]
+    pub referrer_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(mut)]
+    pub referrer:Option<SystemAccount<'info>>,
+
     #[account(
         mut,
         associated_token::mint = rent_fee_mint,
         associated_token::authority = rental_state,
     )]
-    pub rent_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+    pub rent_vault:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
 
     #[account(
         mut,
         associated_token::mint = car_nft_mint,
         associated_token::authority = rental_state,
     )]
-    pub vault:Box<InterfaceAccount<'info,TokenAccount>>,
+    pub vault:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
 
     #[account(
+        mut,
         seeds=[
             b"metadata",
             metadata_program.key().as_ref(),
@@ -69,12 +118,35 @@ pub struct EndRental<'info>{
     )]
     pub master_edition:Box<Account<'info,MasterEditionAccount>>,
 
+    /// CHECK: token-metadata token record of the owner's nft account, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", owner_ata.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub owner_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token-metadata token record of the vault, derivation checked by token-metadata
+    #[account(mut)]
+    pub vault_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: rule set of a programmable nft, checked against the metadata by token-metadata
+    pub authorization_rules:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token auth rules program, checked by token-metadata
+    pub authorization_rules_program:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
+
         #[account(
         mut,
         associated_token::mint = rent_fee_mint,
         associated_token::authority = renter,
     )]
-    pub renter_ata:Box<InterfaceAccount<'info,TokenAccount>>,
+    pub renter_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
 
       #[account(
         mut,
@@ -83,74 +155,221 @@ pub struct EndRental<'info>{
     )]
     pub owner_ata:Box<InterfaceAccount<'info,TokenAccount>>,
 
+    //the renter may have paid in an accepted mint the owner holds no account for yet
       #[account(
-        mut,
+        init_if_needed,
+        payer = authority,
         associated_token::mint = rent_fee_mint,
         associated_token::authority = owner,
     )]
-    pub owner_fee_ata:Box<InterfaceAccount<'info,TokenAccount>>,
+    pub owner_fee_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    //track records of both sides, each pays for their own profile the first time
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_profile:Box<Account<'info,UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", renter.key().as_ref()],
+        bump
+    )]
+    pub renter_profile:Box<Account<'info,UserProfile>>,
+
+    //proof of this completed rental, lets each side rate the other once
+    #[account(
+        init,
+        payer = authority,
+        space = DISCRIMINATOR + RentalReceipt::INIT_SPACE,
+        seeds = [b"receipt", rental_state.key().as_ref(), rental_state.rental_start_time.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub receipt:Box<Account<'info,RentalReceipt>>,
+
+    /// CHECK: renter's rental pass mint, still empty for rentals started before passes were issued
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:UncheckedAccount<'info>,
+
+    /// CHECK: renter's rental pass account
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&renter.key(), &rental_pass_mint.key(), &token_2022_program.key()),
+    )]
+    pub renter_pass:UncheckedAccount<'info>,
 
     pub system_program:Program<'info,System>,
     pub associated_token_program:Program<'info,AssociatedToken>,
     pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
     pub metadata_program:Program<'info,Metadata>,
     pub clock:Sysvar<'info,Clock>,
 }
 
 impl<'info> EndRental<'info>{
 
-    pub fn end_rental(&mut self)->Result<()>{
+    pub fn end_rental(&mut self,bumps:EndRentalBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref().map(|grant| &**grant),OPERATOR_CONFIRM_RETURNS)?;
+
+        //owner side payees first, then transfer hook accounts
+        let (payee_accounts,remaining_accounts) = split_payee_accounts(&self.rental_state,remaining_accounts)?;
+
+        //revoking a legacy nft's delegate on the owner's account takes their signature
+        if self.rental_state.custody == CustodyMode::Frozen {
+            require!(self.owner.is_signer,ErrorCode::OwnerSignatureRequired);
+        }
+
 
         let current_time  = Clock::get()?;
         
         if self.rental_state.renter!=Some(self.renter.key())
         {return Err(ErrorCode::InvalidRenter.into())}
 
+        require!(self.rental_state.fleet.is_none(),ErrorCode::FleetRental);
+
         require!(current_time.unix_timestamp >= self.rental_state.rental_start_time.unwrap(),ErrorCode::RentalPeriodNotEnd);
 
-        //sending nft to owner
-        self.transfer_generic(1, self.car_nft_mint.to_account_info(), self.owner_ata.to_account_info(), self.vault.to_account_info(),self.rental_state.to_account_info(),self.car_nft_mint.decimals)?;
+        //fractionalized cars stay locked in the vault for the next renter
+        if self.rental_state.fraction_pool.is_none() {
+            self.return_nft(remaining_accounts)?;
+        }
 
-        //sending deposit to renter
-        self.transfer_generic(self.rental_state.deposit_amount,self.rent_fee_mint.to_account_info(), self.renter_ata.to_account_info(), self.rent_vault.to_account_info(),self.rental_state.to_account_info(),self.rent_fee_mint.decimals)?;
+        let overage = self.mileage_overage()?;
 
-        //sending rent to owner
-        self.transfer_generic(self.rental_state.rent_fee,self.rent_fee_mint.to_account_info(), self.owner_fee_ata.to_account_info(),self.rent_vault.to_account_info(),self.rental_state.to_account_info(),self.rent_fee_mint.decimals)?;
+        let escrow = Escrow::for_listing(
+            &self.rental_state,
+            self.token_program.to_account_info(),
+            self.system_program.to_account_info(),
+            self.rent_fee_mint.as_deref(),
+            self.rent_vault.as_ref().map(|vault| vault.to_account_info()),
+            self.sol_vault.as_ref().map(|vault| vault.to_account_info()),
+            remaining_accounts,
+        )?;
 
-        self.rental_state.rental_duration = None;
-        self.rental_state.renter = None;
-        self.rental_state.rented = false;
-        self.rental_state.status = StatusData::Finished;
+        let treasury = escrow.payee(self.treasury.as_ref().map(|ata| ata.to_account_info()),self.config.to_account_info())?;
 
-        Ok(())
+        let mut settlement = Settlement{
+            owner_payee:escrow.payee(self.owner_fee_ata.as_ref().map(|ata| ata.to_account_info()),self.owner.to_account_info())?,
+            renter_payee:escrow.payee(self.renter_ata.as_ref().map(|ata| ata.to_account_info()),self.renter.to_account_info())?,
+            pass:RentalPass::new(
+                self.token_2022_program.to_account_info(),
+                self.rental_pass_mint.to_account_info(),
+                self.renter_pass.to_account_info(),
+                self.renter.to_account_info(),
+                self.rental_state.to_account_info(),
+                self.system_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            ),
+            escrow,
+            payee_accounts,
+            owner:self.owner.to_account_info(),
+            rental_state:&mut self.rental_state,
+            owner_profile:&mut self.owner_profile,
+            owner_profile_bump:bumps.owner_profile,
+            renter_profile:&mut self.renter_profile,
+            renter_profile_bump:bumps.renter_profile,
+        };
+
+        //the listing closes here, so the lamport vault's rent reserve goes back to the owner
+        settlement.complete(
+            Fees{
+                config:&self.config,
+                treasury,
+                referrer_stats:self.referrer_stats.as_deref_mut().map(|referrer_stats| &mut **referrer_stats),
+                referrer_fee_ata:self.referrer_fee_ata.as_deref(),
+                referrer_wallet:self.referrer.as_ref().map(|referrer| referrer.to_account_info()),
+            },
+            &mut self.receipt,
+            bumps.receipt,
+            overage,
+            self.owner.to_account_info(),
+        )?;
+
+        if self.rental_state.fraction_pool.is_some() {
+            self.relist();
+            return Ok(());
+        }
+
+        self.rental_state.close(self.owner.to_account_info())
     
     }
 
-    pub fn transfer_generic(&mut self, amount:u64, mint:AccountInfo<'info>, to:AccountInfo<'info>, from:AccountInfo<'info>,authority:AccountInfo<'info>,decimals:u8)->Result<()>{
-        let cpi_program = self.token_program.to_account_info();
+    pub fn car_nft<'a>(&self,remaining_accounts:&'a [AccountInfo<'info>])->CarNft<'a,'info>{
+        CarNft::new(
+            self.token_program.to_account_info(),
+            &self.car_nft_mint,
+            &self.metadata,
+            self.master_edition.to_account_info(),
+            self.authority.to_account_info(),
+            PnftAccounts{
+                metadata_program:self.metadata_program.to_account_info(),
+                system_program:self.system_program.to_account_info(),
+                associated_token_program:self.associated_token_program.to_account_info(),
+                sysvar_instructions:self.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+                authorization_rules_program:self.authorization_rules_program.as_ref().map(|account| account.to_account_info()),
+                authorization_rules:self.authorization_rules.as_ref().map(|account| account.to_account_info()),
+            },
+            remaining_accounts,
+        )
+    }
+
+    pub fn return_nft(&self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
-        let cpi_accounts = TransferChecked{
-            from:from.to_account_info(),
-            to:to.to_account_info(),
-            mint:mint.to_account_info(),
-            authority:authority.to_account_info()
+        let owner_holder = NftHolder{
+            token:self.owner_ata.to_account_info(),
+            owner:self.owner.to_account_info(),
+            token_record:self.owner_token_record.as_ref().map(|account| account.to_account_info()),
         };
 
-        let car_nft_mint = self.car_nft_mint.key();
-        let owner = self.owner.key();
-        let seeds  =[
-            b"rental",
-            car_nft_mint.as_ref(),
-            owner.as_ref(),
-            &[self.rental_state.rental_bump]
-        ];
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        match self.rental_state.custody {
+            CustodyMode::Vault => {
+                let vault = self.vault.as_ref().ok_or(ErrorCode::MissingVault)?;
 
-        let signer_seed = &[&seeds[..]];
+                let vault_holder = NftHolder{
+                    token:vault.to_account_info(),
+                    owner:self.rental_state.to_account_info(),
+                    token_record:self.vault_token_record.as_ref().map(|account| account.to_account_info()),
+                };
 
-        let ctx = CpiContext::new_with_signer(cpi_program,cpi_accounts,signer_seed);
+                self.car_nft(remaining_accounts).transfer(vault_holder,owner_holder,&[&seeds[..]])
+            },
+            CustodyMode::Frozen => self.car_nft(remaining_accounts).unlock(owner_holder,self.rental_state.to_account_info(),&[&seeds[..]]),
+        }
+    }
 
-        transfer_checked(ctx, amount,decimals)?;
+    //charge for the km between the start reading and the device's current one, beyond the included allowance
+    pub fn mileage_overage(&self)->Result<u64>{
+
+        let (Some(mileage),Some(start_odometer)) = (&self.rental_state.mileage,self.rental_state.start_odometer) else {
+            return Ok(0);
+        };
+        let sysvar_instructions = self.sysvar_instructions.as_ref().ok_or(ErrorCode::MissingOdometerReading)?;
 
-        Ok(())
+        let reading = OdometerReading::load(sysvar_instructions,&mileage.device,&self.rental_state.key(),Clock::get()?.unix_timestamp)?;
+        let driven = reading.odometer_km.checked_sub(start_odometer).ok_or(ErrorCode::InvalidOdometerReading)?;
+
+        driven.saturating_sub(mileage.included_km).checked_mul(mileage.fee_per_km).ok_or(ErrorCode::ValueOverflow.into())
+    }
+
+    //clears what the finished rental pinned so the listing can be rented again
+    pub fn relist(&mut self){
+        self.rental_state.rental_start_time = None;
+        self.rental_state.dispute_caller = None;
+        self.rental_state.payment_mint = None;
+        self.rental_state.status = StatusData::Active;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,112 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken, token::Token,
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct FractionalizeCar<'info>{
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    //only classic nft listings, core and compressed assets are always handed back when a rental ends
+    pub car_nft_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one = owner,
+    )]
+    pub rental_state:Box<Account<'info,RentalState>>,
+
+    #[account(
+        constraint = rental_state.rent_fee_mint == Some(rent_fee_mint.key()) @ ErrorCode::CannotFractionalize,
+    )]
+    pub rent_fee_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DISCRIMINATOR + FractionPool::INIT_SPACE,
+        seeds = [b"fraction", rental_state.key().as_ref()],
+        bump
+    )]
+    pub fraction_pool:Box<Account<'info,FractionPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"fraction_shares", rental_state.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = fraction_pool,
+        mint::token_program = share_token_program,
+    )]
+    pub share_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    //named as the listing's only payee, so every settlement pays the owner side in here
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = share_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = share_token_program,
+    )]
+    pub owner_shares:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub share_token_program:Program<'info,Token>,
+}
+
+impl<'info> FractionalizeCar<'info>{
+
+    //locks a vaulted token listing until someone redeems every share, and mints every share to the owner.
+    //holders have to stake to earn, see FractionPool
+    pub fn fractionalize_car(&mut self,total_shares:u64,bumps:FractionalizeCarBumps)->Result<()>{
+
+        let rental_state = &self.rental_state;
+        require!(total_shares > 0,ErrorCode::CannotFractionalize);
+        require!(!rental_state.rented && rental_state.fraction_pool.is_none() && rental_state.fleet.is_none(),ErrorCode::CannotFractionalize);
+        require!(rental_state.custody == CustodyMode::Vault && rental_state.payment_kind == PaymentKind::Token,ErrorCode::CannotFractionalize);
+        //rewards accrue in a single mint
+        require!(rental_state.denomination == PriceDenomination::Token && rental_state.accepted_payments.is_empty() && rental_state.payees.is_empty(),ErrorCode::CannotFractionalize);
+
+        self.fraction_pool.set_inner(FractionPool{
+            version:FractionPool::VERSION,
+            rental_state:self.rental_state.key(),
+            share_mint:self.share_mint.key(),
+            reward_mint:self.rent_fee_mint.key(),
+            total_shares,
+            staked_shares:0,
+            acc_reward_per_share:0,
+            accounted_rewards:0,
+            pool_bump:bumps.fraction_pool,
+        });
+
+        self.rental_state.fraction_pool = Some(self.fraction_pool.key());
+        self.rental_state.payees = vec![Payee{wallet:self.fraction_pool.key(),share_bps:BPS_DENOMINATOR as u16}];
+
+        let seeds = self.fraction_pool.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        mint_to(CpiContext::new_with_signer(self.share_token_program.to_account_info(),MintTo{
+            mint:self.share_mint.to_account_info(),
+            to:self.owner_shares.to_account_info(),
+            authority:self.fraction_pool.to_account_info(),
+        },&[&seeds[..]]),total_shares)
+    }
+}
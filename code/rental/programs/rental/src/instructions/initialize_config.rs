@@ -0,0 +1,60 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*, program::Rental};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    //the protocol singletons can only be created by whoever deployed the program
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program:Program<'info,Rental>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program_data:Account<'info,ProgramData>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = DISCRIMINATOR + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config:Account<'info,Config>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> InitializeConfig<'info>{
+    pub fn initialize_config(&mut self,params:ConfigParams,bumps:InitializeConfigBumps)->Result<()>{
+
+        validate_config_params(&params)?;
+
+        self.config.set_inner(Config{
+            version:Config::VERSION,
+            admin:self.multisig.key(),
+            arbitrator:params.arbitrator,
+            protocol_fee_bps:params.protocol_fee_bps,
+            referral_fee_bps:params.referral_fee_bps,
+            timelock:params.timelock,
+            pending:None,
+            config_bump:bumps.config,
+        });
+
+        Ok(())
+    }
+}
+
+pub fn validate_config_params(params:&ConfigParams)->Result<()>{
+    require!(params.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,ErrorCode::InvalidProtocolFee);
+    require!(params.referral_fee_bps <= MAX_REFERRAL_FEE_BPS,ErrorCode::InvalidReferralFee);
+    require!(params.timelock >= MIN_CONFIG_TIMELOCK,ErrorCode::InvalidTimelock);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*, program::Rental};
+
+#[derive(Accounts)]
+pub struct InitializeMultisig<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    //the protocol singletons can only be created by whoever deployed the program
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program:Program<'info,Rental>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program_data:Account<'info,ProgramData>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = DISCRIMINATOR + Multisig::INIT_SPACE,
+        seeds = [b"multisig"],
+        bump
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> InitializeMultisig<'info>{
+    pub fn initialize_multisig(&mut self,members:Vec<Pubkey>,threshold:u8,bumps:InitializeMultisigBumps)->Result<()>{
+
+        Multisig::validate_members(&members,threshold)?;
+
+        self.multisig.set_inner(Multisig{
+            version:Multisig::VERSION,
+            members,
+            threshold,
+            proposal_count:0,
+            multisig_bump:bumps.multisig,
+        });
+
+        Ok(())
+    }
+}
@@ -1,10 +1,69 @@
-use anchor_lang::{prelude::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
     associated_token::AssociatedToken, metadata::{MasterEditionAccount, Metadata, MetadataAccount},
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+    token_interface::{Mint, TokenAccount, TokenInterface}
 };
-use crate::{state::*,errors::ErrorCode, constants::*};
+use crate::{state::*,errors::ErrorCode, constants::*, utils::{fund_sol_vault, CarNft, NftHolder, PnftAccounts}};
 
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct ListingOptions{
+    pub referral_fee_bps:Option<u16>,
+    pub transfer_fee_payer:TransferFeePayer,
+    pub payment_kind:PaymentKind,
+    pub custody:CustodyMode,
+    pub denomination:PriceDenomination,
+    pub accepted_payments:Vec<AcceptedPayment>,
+    pub deposit_tiers:Vec<DepositTier>,
+    pub required_claims:u32,
+    pub allowlist:Option<Pubkey>,
+    pub blocklist:Option<Pubkey>,
+    pub payees:Vec<Payee>,
+}
+
+impl ListingOptions{
+    pub fn validate(&self)->Result<()>{
+        if let Some(referral_fee_bps) = self.referral_fee_bps {
+            require!(referral_fee_bps <= MAX_REFERRAL_FEE_BPS,ErrorCode::InvalidReferralFee);
+        }
+
+        self.validate_payments()?;
+        self.validate_deposit_tiers()?;
+        self.validate_payees()
+    }
+
+    //alternative mints carry their own token prices, so only token-priced token listings can take them
+    pub fn validate_payments(&self)->Result<()>{
+        if self.accepted_payments.is_empty() {
+            return Ok(());
+        }
+
+        require!(self.accepted_payments.len() <= MAX_ACCEPTED_PAYMENTS,ErrorCode::TooManyPaymentMints);
+        require!(self.payment_kind == PaymentKind::Token && self.denomination == PriceDenomination::Token,ErrorCode::InvalidPaymentMint);
+
+        Ok(())
+    }
+
+    pub fn validate_payees(&self)->Result<()>{
+        if self.payees.is_empty() {
+            return Ok(());
+        }
+
+        require!(self.payees.len() <= MAX_PAYEES,ErrorCode::InvalidPayees);
+        for (i,payee) in self.payees.iter().enumerate(){
+            require!(payee.share_bps > 0 && !self.payees[..i].iter().any(|other| other.wallet == payee.wallet),ErrorCode::InvalidPayees);
+        }
+        require!(self.payees.iter().map(|payee| payee.share_bps as u64).sum::<u64>() == BPS_DENOMINATOR,ErrorCode::InvalidPayees);
+
+        Ok(())
+    }
+
+    pub fn validate_deposit_tiers(&self)->Result<()>{
+        require!(self.deposit_tiers.len() <= MAX_DEPOSIT_TIERS,ErrorCode::TooManyDepositTiers);
+        require!(self.deposit_tiers.iter().all(|tier| tier.discount_bps as u64 <= BPS_DENOMINATOR),ErrorCode::InvalidDepositTier);
+
+        Ok(())
+    }
+}
 
 #[derive(Accounts)]
 pub struct ListCar<'info>{
@@ -15,7 +74,7 @@ pub struct ListCar<'info>{
     pub car_nft_mint:InterfaceAccount<'info,Mint>,
 
     pub collection_mint:InterfaceAccount<'info,Mint>,
-    pub rent_fee_mint:InterfaceAccount<'info,Mint>,
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
 
     #[account(
         init,
@@ -40,7 +99,7 @@ pub struct ListCar<'info>{
         associated_token::mint = car_nft_mint,
         associated_token::authority = rental_state
     )]
-    pub vault:InterfaceAccount<'info,TokenAccount>,
+    pub vault:Option<InterfaceAccount<'info,TokenAccount>>,
 
       #[account(
         init_if_needed,
@@ -48,9 +107,17 @@ pub struct ListCar<'info>{
         associated_token::mint = rent_fee_mint,
         associated_token::authority = owner,
     )]
-    pub owner_fee_ata:InterfaceAccount<'info,TokenAccount>,
+    pub owner_fee_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
 
     #[account(
+        mut,
         seeds=[
             b"metadata",
             metadata_program.key().as_ref(),
@@ -71,6 +138,29 @@ pub struct ListCar<'info>{
     )]
     pub master_edition:Account<'info,MasterEditionAccount>,
 
+    /// CHECK: token-metadata token record of the owner's nft account, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", owner_nft_account.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub owner_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token-metadata token record of the vault, derivation checked by token-metadata
+    #[account(mut)]
+    pub vault_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: rule set of a programmable nft, checked against the metadata by token-metadata
+    pub authorization_rules:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token auth rules program, checked by token-metadata
+    pub authorization_rules_program:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
+
     pub system_program:Program<'info,System>,
     pub associated_token_program:Program<'info,AssociatedToken>,
     pub token_program:Interface<'info,TokenInterface>,
@@ -79,52 +169,88 @@ pub struct ListCar<'info>{
 }
 
 impl<'info> ListCar<'info>{
-    pub fn list_car(&mut self,rent_fee:u64,  deposit_amount:u64, bumps:ListCarBumps)->Result<()>{
+    pub fn list_car(&mut self,rent_fee:u64,  deposit_amount:u64, options:ListingOptions, bumps:ListCarBumps, remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
-        self.update_state(rent_fee,deposit_amount,bumps)?;
-
-        self.transfer_nft()?;
-
-        Ok(())
+        options.validate()?;
 
-    }
+        match options.payment_kind {
+            PaymentKind::Token => require!(self.rent_fee_mint.is_some() && self.owner_fee_ata.is_some(),ErrorCode::MissingPaymentAccounts),
+            PaymentKind::Lamports => fund_sol_vault(self.system_program.to_account_info(),self.owner.to_account_info(),self.sol_vault.as_ref().map(|vault| vault.to_account_info()))?,
+        }
 
-    pub fn update_state(&mut self,rent_fee:u64,  deposit_amount:u64, bumps:ListCarBumps)->Result<()>{
+        let custody = options.custody;
 
-        self.rental_state.set_inner(RentalState{
-            owner:self.owner.key(),
-            renter:None,
-            rental_duration:None,
-            car_nft_mint:self.car_nft_mint.key(),
+        self.rental_state.set_inner(RentalState::new_listing(
+            self.owner.key(),
+            self.car_nft_mint.key(),
+            self.rent_fee_mint.as_ref().map(|mint| mint.key()),
             rent_fee,
-            rental_start_time:None,
             deposit_amount,
-            rental_bump : bumps.rental_state,
-            listed:true,
-            rented:false,
-            status:StatusData::Active,
-            dispute_caller:None,
-            test_num:None
-        });
+            options,
+            (bumps.rental_state,bumps.sol_vault),
+        ));
+
+        match custody {
+            CustodyMode::Vault => self.transfer_nft(remaining_accounts)?,
+            CustodyMode::Frozen => self.freeze_nft(remaining_accounts)?,
+        }
 
         Ok(())
+
     }
 
-    pub fn transfer_nft(&mut self)->Result<()>{
+    pub fn car_nft<'a>(&self,remaining_accounts:&'a [AccountInfo<'info>])->CarNft<'a,'info>{
+        CarNft::new(
+            self.token_program.to_account_info(),
+            &self.car_nft_mint,
+            &self.metadata,
+            self.master_edition.to_account_info(),
+            self.owner.to_account_info(),
+            PnftAccounts{
+                metadata_program:self.metadata_program.to_account_info(),
+                system_program:self.system_program.to_account_info(),
+                associated_token_program:self.associated_token_program.to_account_info(),
+                sysvar_instructions:self.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+                authorization_rules_program:self.authorization_rules_program.as_ref().map(|account| account.to_account_info()),
+                authorization_rules:self.authorization_rules.as_ref().map(|account| account.to_account_info()),
+            },
+            remaining_accounts,
+        )
+    }
+
+    pub fn transfer_nft(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
-        let cpi_program = self.token_program.to_account_info();
+        let vault = self.vault.as_ref().ok_or(ErrorCode::MissingVault)?;
 
-        let cpi_accounts = TransferChecked{
-            from:self.owner_nft_account.to_account_info(),
-            to:self.vault.to_account_info(),
-            mint:self.car_nft_mint.to_account_info(),
-            authority:self.owner.to_account_info()
+        let from = NftHolder{
+            token:self.owner_nft_account.to_account_info(),
+            owner:self.owner.to_account_info(),
+            token_record:self.owner_token_record.as_ref().map(|account| account.to_account_info()),
         };
 
-        let ctx = CpiContext::new(cpi_program,cpi_accounts);
+        let to = NftHolder{
+            token:vault.to_account_info(),
+            owner:self.rental_state.to_account_info(),
+            token_record:self.vault_token_record.as_ref().map(|account| account.to_account_info()),
+        };
 
-        transfer_checked(ctx, 1,0)?;
+        self.car_nft(remaining_accounts).transfer(from,to,&[])?;
 
         Ok(())
     }
+
+    //the nft stays in the owner's wallet, frozen with the rental_state as its delegate
+    pub fn freeze_nft(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        let holder = NftHolder{
+            token:self.owner_nft_account.to_account_info(),
+            owner:self.owner.to_account_info(),
+            token_record:self.owner_token_record.as_ref().map(|account| account.to_account_info()),
+        };
+
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        self.car_nft(remaining_accounts).lock(holder,self.rental_state.to_account_info(),&[&seeds[..]])
+    }
 }
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+use crate::{state::*,errors::ErrorCode, constants::*, instructions::ListingOptions, utils::{fund_sol_vault, CompressedCar, CompressedLeaf, BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID}};
+
+#[derive(Accounts)]
+#[instruction(rent_fee:u64, deposit_amount:u64, options:ListingOptions, leaf:CompressedLeaf)]
+pub struct ListCompressedCar<'info>{
+
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    pub collection_mint:InterfaceAccount<'info,Mint>,
+
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DISCRIMINATOR + RentalState::INIT_SPACE,
+        seeds = [b"rental", leaf.asset_id.as_ref(),owner.key().as_ref()],
+        bump
+    )]
+    pub rental_state:Account<'info,RentalState>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_fee_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    /// CHECK: bubblegum tree config
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum_program.key(),
+        bump
+    )]
+    pub tree_config:UncheckedAccount<'info>,
+
+    /// CHECK: concurrent merkle tree holding the car, the leaf is proven by bubblegum
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree:UncheckedAccount<'info>,
+
+    /// CHECK: spl noop program
+    #[account(address = SPL_NOOP_ID)]
+    pub log_wrapper:UncheckedAccount<'info>,
+
+    /// CHECK: spl account compression program
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program:UncheckedAccount<'info>,
+
+    /// CHECK: bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+}
+
+impl<'info> ListCompressedCar<'info>{
+    pub fn list_compressed_car(&mut self,rent_fee:u64,deposit_amount:u64,options:ListingOptions,leaf:CompressedLeaf,bumps:ListCompressedCarBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        leaf.verify_asset_id(&self.merkle_tree.key())?;
+        leaf.verify_collection(&self.collection_mint.key())?;
+
+        require!(options.custody == CustodyMode::Vault,ErrorCode::UnsupportedCustody);
+
+        options.validate()?;
+
+        match options.payment_kind {
+            PaymentKind::Token => require!(self.rent_fee_mint.is_some() && self.owner_fee_ata.is_some(),ErrorCode::MissingPaymentAccounts),
+            PaymentKind::Lamports => fund_sol_vault(self.system_program.to_account_info(),self.owner.to_account_info(),self.sol_vault.as_ref().map(|vault| vault.to_account_info()))?,
+        }
+
+        self.rental_state.set_inner(RentalState::new_listing(
+            self.owner.key(),
+            leaf.asset_id,
+            self.rent_fee_mint.as_ref().map(|mint| mint.key()),
+            rent_fee,
+            deposit_amount,
+            options,
+            (bumps.rental_state,bumps.sol_vault),
+        ));
+
+        //the leaf owner becomes the rental_state
+        let (proof,_) = leaf.split_proof(remaining_accounts)?;
+        self.compressed_car().transfer(&leaf,proof,self.owner.to_account_info(),self.rental_state.to_account_info(),&[])?;
+
+        Ok(())
+    }
+
+    pub fn compressed_car(&self)->CompressedCar<'info>{
+        CompressedCar{
+            bubblegum_program:self.bubblegum_program.to_account_info(),
+            tree_config:self.tree_config.to_account_info(),
+            merkle_tree:self.merkle_tree.to_account_info(),
+            log_wrapper:self.log_wrapper.to_account_info(),
+            compression_program:self.compression_program.to_account_info(),
+            system_program:self.system_program.to_account_info(),
+        }
+    }
+}
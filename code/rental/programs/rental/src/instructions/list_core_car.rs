@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+use crate::{state::*,errors::ErrorCode, constants::*, instructions::ListingOptions, utils::{fund_sol_vault, CoreAsset, CoreCar, MPL_CORE_PROGRAM_ID}};
+
+#[derive(Accounts)]
+pub struct ListCoreCar<'info>{
+
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    /// CHECK: mpl-core asset, parsed and checked in the handler
+    #[account(mut, owner = MPL_CORE_PROGRAM_ID)]
+    pub asset:UncheckedAccount<'info>,
+
+    /// CHECK: mpl-core collection, checked against the asset in the handler
+    pub collection:UncheckedAccount<'info>,
+
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DISCRIMINATOR + RentalState::INIT_SPACE,
+        seeds = [b"rental", asset.key().as_ref(),owner.key().as_ref()],
+        bump
+    )]
+    pub rental_state:Account<'info,RentalState>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_fee_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    /// CHECK: mpl-core program
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub core_program:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+}
+
+impl<'info> ListCoreCar<'info>{
+    pub fn list_core_car(&mut self,rent_fee:u64,deposit_amount:u64,options:ListingOptions,bumps:ListCoreCarBumps)->Result<()>{
+
+        let core_asset = CoreAsset::load(&self.asset)?;
+        require_keys_eq!(core_asset.owner,self.owner.key(),ErrorCode::InvalidAssetOwner);
+        core_asset.verify_collection(&self.collection)?;
+
+        require!(options.custody == CustodyMode::Vault,ErrorCode::UnsupportedCustody);
+
+        options.validate()?;
+
+        match options.payment_kind {
+            PaymentKind::Token => require!(self.rent_fee_mint.is_some() && self.owner_fee_ata.is_some(),ErrorCode::MissingPaymentAccounts),
+            PaymentKind::Lamports => fund_sol_vault(self.system_program.to_account_info(),self.owner.to_account_info(),self.sol_vault.as_ref().map(|vault| vault.to_account_info()))?,
+        }
+
+        self.rental_state.set_inner(RentalState::new_listing(
+            self.owner.key(),
+            self.asset.key(),
+            self.rent_fee_mint.as_ref().map(|mint| mint.key()),
+            rent_fee,
+            deposit_amount,
+            options,
+            (bumps.rental_state,bumps.sol_vault),
+        ));
+
+        //custody is the asset's owner field, so the rental_state takes ownership
+        self.core_car().transfer(self.owner.to_account_info(),self.rental_state.to_account_info(),&[])?;
+
+        Ok(())
+    }
+
+    pub fn core_car(&self)->CoreCar<'info>{
+        CoreCar{
+            core_program:self.core_program.to_account_info(),
+            asset:self.asset.to_account_info(),
+            collection:self.collection.to_account_info(),
+            payer:self.owner.to_account_info(),
+            system_program:self.system_program.to_account_info(),
+        }
+    }
+}
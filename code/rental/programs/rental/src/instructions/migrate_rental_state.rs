@@ -0,0 +1,79 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct MigrateRentalState<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    /// CHECK: holds an older layout that Account<RentalState> cannot decode, discriminator and layout are checked in migrate_rental_state
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub rental_state:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> MigrateRentalState<'info>{
+    pub fn migrate_rental_state(&mut self)->Result<()>{
+
+        let migrated = self.read_old_layout()?;
+
+        realloc_account(
+            self.rental_state.to_account_info(),
+            self.payer.to_account_info(),
+            self.system_program.to_account_info(),
+            DISCRIMINATOR + RentalState::INIT_SPACE
+        )?;
+
+        let info = self.rental_state.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer:&mut [u8] = &mut data[..];
+        migrated.try_serialize(&mut writer)?;
+
+        Ok(())
+    }
+
+    pub fn read_old_layout(&self)->Result<RentalState>{
+
+        let data = self.rental_state.try_borrow_data()?;
+        require!(data.len() > DISCRIMINATOR && data[..DISCRIMINATOR] == *RentalState::DISCRIMINATOR,ErrorCode::InvalidAccountLayout);
+
+        let mut body = &data[DISCRIMINATOR..];
+
+        //unversioned accounts are recognised by their fixed allocation size
+        if data.len() == DISCRIMINATOR + RentalStateV0::INIT_SPACE {
+            return Ok(RentalStateV0::deserialize(&mut body)?.into());
+        }
+
+        match data[DISCRIMINATOR] {
+            RentalState::VERSION => err!(ErrorCode::AlreadyMigrated),
+            _ => err!(ErrorCode::InvalidAccountLayout),
+        }
+    }
+}
+
+//tops the account up to rent exemption for its new size before resizing it
+pub fn realloc_account<'info>(account:AccountInfo<'info>,payer:AccountInfo<'info>,system_program:AccountInfo<'info>,new_len:usize)->Result<()>{
+
+    let required = Rent::get()?.minimum_balance(new_len);
+    let current = account.lamports();
+
+    if required > current {
+        let cpi_accounts = Transfer{
+            from:payer,
+            to:account.clone(),
+        };
+
+        let ctx = CpiContext::new(system_program,cpi_accounts);
+
+        transfer(ctx, required - current)?;
+    }
+
+    account.resize(new_len)?;
+
+    Ok(())
+}
@@ -8,4 +8,134 @@ pub mod emergency_exit;
 pub use emergency_exit::*;
 
 pub mod end_rental;
-pub use end_rental::*;
\ No newline at end of file
+pub use end_rental::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod propose_config;
+pub use propose_config::*;
+
+pub mod apply_config;
+pub use apply_config::*;
+
+pub mod cancel_config;
+pub use cancel_config::*;
+
+pub mod initialize_multisig;
+pub use initialize_multisig::*;
+
+pub mod create_proposal;
+pub use create_proposal::*;
+
+pub mod approve_proposal;
+pub use approve_proposal::*;
+
+pub mod update_multisig;
+pub use update_multisig::*;
+
+pub mod withdraw_treasury;
+pub use withdraw_treasury::*;
+
+pub mod migrate_rental_state;
+pub use migrate_rental_state::*;
+
+
+pub mod register_referrer;
+pub use register_referrer::*;
+
+pub mod withdraw_treasury_lamports;
+pub use withdraw_treasury_lamports::*;
+
+pub mod list_core_car;
+pub use list_core_car::*;
+
+pub mod end_core_rental;
+pub use end_core_rental::*;
+
+pub mod core_emergency_exit;
+pub use core_emergency_exit::*;
+
+pub mod list_compressed_car;
+pub use list_compressed_car::*;
+
+pub mod end_compressed_rental;
+pub use end_compressed_rental::*;
+
+pub mod compressed_emergency_exit;
+pub use compressed_emergency_exit::*;
+
+pub mod create_fleet;
+pub use create_fleet::*;
+
+pub mod add_fleet_car;
+pub use add_fleet_car::*;
+
+pub mod remove_fleet_car;
+pub use remove_fleet_car::*;
+
+pub mod rent_fleet_car;
+pub use rent_fleet_car::*;
+
+pub mod end_fleet_rental;
+pub use end_fleet_rental::*;
+
+pub mod set_price_feed;
+pub use set_price_feed::*;
+
+pub mod open_dispute;
+pub use open_dispute::*;
+
+pub mod rate_rental;
+pub use rate_rental::*;
+
+pub mod set_attestation_issuer;
+pub use set_attestation_issuer::*;
+
+pub mod write_attestation;
+pub use write_attestation::*;
+
+pub mod create_renter_list;
+pub use create_renter_list::*;
+
+pub mod update_renter_list;
+pub use update_renter_list::*;
+
+pub mod ban_wallet;
+pub use ban_wallet::*;
+
+pub mod unban_wallet;
+pub use unban_wallet::*;
+
+pub mod set_operator;
+pub use set_operator::*;
+
+pub mod revoke_operator;
+pub use revoke_operator::*;
+
+pub mod update_listing_price;
+pub use update_listing_price::*;
+
+pub mod fractionalize_car;
+pub use fractionalize_car::*;
+
+pub mod stake_shares;
+pub use stake_shares::*;
+
+pub mod unstake_shares;
+pub use unstake_shares::*;
+
+pub mod claim_share_rewards;
+pub use claim_share_rewards::*;
+
+pub mod set_mileage_pricing;
+pub use set_mileage_pricing::*;
+
+pub mod approve_referrer;
+pub use approve_referrer::*;
+
+pub mod redeem_car;
+pub use redeem_car::*;
+
+pub mod claim_overdue;
+pub use claim_overdue::*;
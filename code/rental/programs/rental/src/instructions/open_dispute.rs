@@ -0,0 +1,62 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info>{
+    #[account(mut)]
+    pub caller:Signer<'info>,
+
+    /// CHECK: side of the rental the caller acts for, the caller itself or the owner it operates for
+    pub party:UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"operator", party.key().as_ref(), caller.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Account<'info,OperatorGrant>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental", rental_state.car_nft_mint.as_ref(), rental_state.owner.as_ref()],
+        bump = rental_state.rental_bump,
+    )]
+    pub rental_state:Account<'info,RentalState>,
+
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = DISCRIMINATOR + UserProfile::INIT_SPACE,
+        seeds = [b"profile", party.key().as_ref()],
+        bump
+    )]
+    pub party_profile:Account<'info,UserProfile>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> OpenDispute<'info>{
+
+    //either side of an active rental can flag it for the arbitrator, who settles it through emergency_exit
+    pub fn open_dispute(&mut self,bumps:OpenDisputeBumps)->Result<()>{
+
+        let party = self.party.key();
+        require!(party == self.rental_state.owner || Some(party) == self.rental_state.renter,ErrorCode::NotRentalParty);
+
+        //only the owner side can be operated
+        if party != self.caller.key() {
+            require!(party == self.rental_state.owner,ErrorCode::NotRentalParty);
+            OperatorGrant::require_permission(&party,&self.caller.key(),self.operator_grant.as_deref(),OPERATOR_DISPUTES)?;
+        }
+
+        require!(self.rental_state.rented,ErrorCode::InvalidRenter);
+        require!(self.rental_state.status == StatusData::Active,ErrorCode::DisputeAlreadyInitiated);
+        require!(self.rental_state.fleet.is_none(),ErrorCode::FleetRental);
+
+        self.rental_state.status = StatusData::Dispute;
+        self.rental_state.dispute_caller = Some(party);
+
+        self.party_profile.init_if_new(party,bumps.party_profile);
+        self.party_profile.record_dispute_opened()
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, instructions::validate_config_params};
+
+#[derive(Accounts)]
+pub struct ProposeConfig<'info>{
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+}
+
+impl<'info> ProposeConfig<'info>{
+    pub fn propose_config(&mut self)->Result<()>{
+
+        let ProposalAction::ProposeConfig { params } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        self.proposal.execute(&self.multisig)?;
+
+        require!(self.config.pending.is_none(),ErrorCode::ConfigUpdatePending);
+        validate_config_params(&params)?;
+
+        //the delay is the one currently in force, so shortening the timelock is itself timelocked
+        let current_time = Clock::get()?;
+        let effective_at = current_time.unix_timestamp.checked_add(self.config.timelock).ok_or(ErrorCode::ValueOverflow)?;
+
+        self.config.pending = Some(PendingConfig{
+            params,
+            effective_at,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct RateRental<'info>{
+    #[account(mut)]
+    pub rater:Signer<'info>,
+
+    /// CHECK: paid for the receipt, gets its rent back once both sides have rated
+    #[account(
+        mut,
+        address = receipt.owner,
+    )]
+    pub owner:UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.rental_state.as_ref(), receipt.rental_start_time.to_le_bytes().as_ref()],
+        bump = receipt.receipt_bump,
+    )]
+    pub receipt:Account<'info,RentalReceipt>,
+
+    //the other party's profile, created when the rental was settled
+    #[account(
+        mut,
+        seeds = [b"profile", ratee_profile.user.as_ref()],
+        bump = ratee_profile.profile_bump,
+    )]
+    pub ratee_profile:Account<'info,UserProfile>,
+}
+
+impl<'info> RateRental<'info>{
+
+    //each side of a completed rental rates the other once, scores only land on the counterparty's profile
+    pub fn rate_rental(&mut self,score:u8,review_hash:Option<[u8;32]>)->Result<()>{
+
+        require!((MIN_RATING..=MAX_RATING).contains(&score),ErrorCode::InvalidRating);
+        require_keys_neq!(self.receipt.owner,self.receipt.renter,ErrorCode::NotRentalParty);
+
+        let rater = self.rater.key();
+        let (owner,renter) = (self.receipt.owner,self.receipt.renter);
+        let receipt:&mut RentalReceipt = &mut self.receipt;
+
+        let (slot,ratee) = if rater == owner {
+            (&mut receipt.owner_rating,renter)
+        } else if rater == renter {
+            (&mut receipt.renter_rating,owner)
+        } else {
+            return err!(ErrorCode::NotRentalParty);
+        };
+
+        require!(slot.is_none(),ErrorCode::AlreadyRated);
+        require_keys_eq!(self.ratee_profile.user,ratee,ErrorCode::NotRentalParty);
+
+        *slot = Some(Rating{score,review_hash});
+        self.ratee_profile.record_rating(score)?;
+
+        //both sides are done, nothing left to guard
+        if self.receipt.owner_rating.is_some() && self.receipt.renter_rating.is_some() {
+            self.receipt.close(self.owner.to_account_info())?;
+        }
+
+        Ok(())
+    }
+}
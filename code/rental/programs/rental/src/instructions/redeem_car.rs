@@ -0,0 +1,197 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken, metadata::{MasterEditionAccount, Metadata, MetadataAccount}, token::Token,
+    token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, utils::{CarNft, NftHolder, PnftAccounts}};
+
+#[derive(Accounts)]
+pub struct RedeemCar<'info>{
+    //whoever holds every share, not necessarily the listing's owner
+    #[account(mut)]
+    pub holder:Signer<'info>,
+
+    //listing owner, gets the listing's and pool's rent back
+    #[account(mut)]
+    pub owner:SystemAccount<'info>,
+
+    pub car_nft_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub rental_state:Box<Account<'info,RentalState>>,
+
+    #[account(
+        mut,
+        seeds = [b"fraction", rental_state.key().as_ref()],
+        bump = fraction_pool.pool_bump,
+        close = owner,
+    )]
+    pub fraction_pool:Box<Account<'info,FractionPool>>,
+
+    #[account(mut, address = fraction_pool.share_mint)]
+    pub share_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = share_token_program,
+    )]
+    pub holder_shares:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(address = fraction_pool.reward_mint)]
+    pub reward_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_rewards:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = car_nft_mint,
+        associated_token::authority = rental_state,
+    )]
+    pub vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = car_nft_mint,
+        associated_token::authority = holder,
+    )]
+    pub holder_nft_account:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"metadata",
+            metadata_program.key().as_ref(),
+            car_nft_mint.key().as_ref()
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata:Box<Account<'info,MetadataAccount>>,
+
+    #[account(
+        seeds=[b"metadata",metadata_program.key().as_ref(),car_nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = metadata_program.key()
+    )]
+    pub master_edition:Box<Account<'info,MasterEditionAccount>>,
+
+    /// CHECK: token-metadata token record of the holder's nft account, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", holder_nft_account.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub holder_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token-metadata token record of the vault, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", vault.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub vault_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: rule set of a programmable nft, checked against the metadata by token-metadata
+    pub authorization_rules:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token auth rules program, checked by token-metadata
+    pub authorization_rules_program:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub share_token_program:Program<'info,Token>,
+    pub metadata_program:Program<'info,Metadata>,
+}
+
+impl<'info> RedeemCar<'info>{
+
+    //burning every share unlocks the car to the holder along with whatever rent is still in the reward vault, the listing closes with it.
+    //shares can't be gathered while any are staked, so every position has already been settled by the time this runs
+    pub fn redeem_car(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        require!(!self.rental_state.rented,ErrorCode::CarAlreadyRented);
+        require!(self.holder_shares.amount == self.fraction_pool.total_shares,ErrorCode::InsufficientShares);
+
+        burn(CpiContext::new(self.share_token_program.to_account_info(),Burn{
+            mint:self.share_mint.to_account_info(),
+            from:self.holder_shares.to_account_info(),
+            authority:self.holder.to_account_info(),
+        }),self.fraction_pool.total_shares)?;
+
+        //unclaimed rent, the part earned while shares sat unstaked and rounding dust left in the pool
+        FractionPool::pay_rewards(&self.fraction_pool,self.token_program.to_account_info(),self.reward_vault.to_account_info(),&self.reward_mint,self.holder_rewards.to_account_info(),self.reward_vault.amount,remaining_accounts)?;
+
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        let (holder,vault) = self.holders();
+        self.car_nft(remaining_accounts).transfer(vault,holder,&[&seeds[..]])
+    }
+
+    pub fn car_nft<'a>(&self,remaining_accounts:&'a [AccountInfo<'info>])->CarNft<'a,'info>{
+        CarNft::new(
+            self.token_program.to_account_info(),
+            &self.car_nft_mint,
+            &self.metadata,
+            self.master_edition.to_account_info(),
+            self.holder.to_account_info(),
+            PnftAccounts{
+                metadata_program:self.metadata_program.to_account_info(),
+                system_program:self.system_program.to_account_info(),
+                associated_token_program:self.associated_token_program.to_account_info(),
+                sysvar_instructions:self.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+                authorization_rules_program:self.authorization_rules_program.as_ref().map(|account| account.to_account_info()),
+                authorization_rules:self.authorization_rules.as_ref().map(|account| account.to_account_info()),
+            },
+            remaining_accounts,
+        )
+    }
+
+    pub fn holders(&self)->(NftHolder<'info>,NftHolder<'info>){
+        let holder = NftHolder{
+            token:self.holder_nft_account.to_account_info(),
+            owner:self.holder.to_account_info(),
+            token_record:self.holder_token_record.as_ref().map(|account| account.to_account_info()),
+        };
+
+        let vault = NftHolder{
+            token:self.vault.to_account_info(),
+            owner:self.rental_state.to_account_info(),
+            token_record:self.vault_token_record.as_ref().map(|account| account.to_account_info()),
+        };
+
+        (holder,vault)
+    }
+}
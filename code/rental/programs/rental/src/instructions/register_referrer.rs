@@ -0,0 +1,37 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*, constants::*};
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info>{
+    #[account(mut)]
+    pub referrer:Signer<'info>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = DISCRIMINATOR + ReferrerStats::INIT_SPACE,
+        seeds = [b"referrer", referrer.key().as_ref()],
+        bump
+    )]
+    pub referrer_stats:Account<'info,ReferrerStats>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> RegisterReferrer<'info>{
+    pub fn register_referrer(&mut self,bumps:RegisterReferrerBumps)->Result<()>{
+
+        self.referrer_stats.set_inner(ReferrerStats{
+            version:ReferrerStats::VERSION,
+            referrer:self.referrer.key(),
+            rentals_referred:0,
+            rentals_settled:0,
+            total_earned:0,
+            referrer_bump:bumps.referrer_stats,
+            approved:false,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken, metadata::{MasterEditionAccount, Metadata, MetadataAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{state::*,errors::ErrorCode, utils::{CarNft, NftHolder, PnftAccounts}};
+
+#[derive(Accounts)]
+pub struct RemoveFleetCar<'info>{
+
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    pub car_nft_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"fleet", owner.key().as_ref(), fleet.collection_mint.as_ref()],
+        bump = fleet.fleet_bump,
+        has_one = owner,
+    )]
+    pub fleet:Box<Account<'info,FleetPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = car_nft_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_nft_account:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = car_nft_mint,
+        associated_token::authority = fleet
+    )]
+    pub fleet_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"metadata",
+            metadata_program.key().as_ref(),
+            car_nft_mint.key().as_ref()
+        ],
+        seeds::program = metadata_program.key(),
+        bump,
+        constraint = metadata.collection.is_some() @ ErrorCode::MissingCollection,
+        constraint = metadata.collection.as_ref().unwrap().key == fleet.collection_mint @ ErrorCode::InvalidCollection,
+        constraint = metadata.collection.as_ref().unwrap().verified @ ErrorCode::UnverifiedCollection
+    )]
+    pub metadata:Box<Account<'info,MetadataAccount>>,
+
+    #[account(
+        seeds=[b"metadata",metadata_program.key().as_ref(),car_nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = metadata_program.key()
+    )]
+    pub master_edition:Box<Account<'info,MasterEditionAccount>>,
+
+    /// CHECK: token-metadata token record of the owner's nft account, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", owner_nft_account.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub owner_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token-metadata token record of the fleet vault, only used for programmable nfts
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), car_nft_mint.key().as_ref(), b"token_record", fleet_vault.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump
+    )]
+    pub fleet_vault_token_record:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: rule set of a programmable nft, checked against the metadata by token-metadata
+    pub authorization_rules:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: token auth rules program, checked by token-metadata
+    pub authorization_rules_program:Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> RemoveFleetCar<'info>{
+    pub fn remove_fleet_car(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        //rented units come back through end_fleet_rental first
+        let car_nft_mint = self.car_nft_mint.key();
+        let position = self.fleet.available.iter().position(|unit| *unit == car_nft_mint).ok_or(ErrorCode::FleetUnitUnavailable)?;
+        self.fleet.available.swap_remove(position);
+
+        let seeds = self.fleet.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        let (owner_holder,fleet_holder) = self.holders();
+        self.car_nft(remaining_accounts).transfer(fleet_holder,owner_holder,&[&seeds[..]])?;
+
+        Ok(())
+    }
+
+    pub fn car_nft<'a>(&self,remaining_accounts:&'a [AccountInfo<'info>])->CarNft<'a,'info>{
+        CarNft::new(
+            self.token_program.to_account_info(),
+            &self.car_nft_mint,
+            &self.metadata,
+            self.master_edition.to_account_info(),
+            self.owner.to_account_info(),
+            PnftAccounts{
+                metadata_program:self.metadata_program.to_account_info(),
+                system_program:self.system_program.to_account_info(),
+                associated_token_program:self.associated_token_program.to_account_info(),
+                sysvar_instructions:self.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+                authorization_rules_program:self.authorization_rules_program.as_ref().map(|account| account.to_account_info()),
+                authorization_rules:self.authorization_rules.as_ref().map(|account| account.to_account_info()),
+            },
+            remaining_accounts,
+        )
+    }
+
+    pub fn holders(&self)->(NftHolder<'info>,NftHolder<'info>){
+        let owner_holder = NftHolder{
+            token:self.owner_nft_account.to_account_info(),
+            owner:self.owner.to_account_info(),
+            token_record:self.owner_token_record.as_ref().map(|account| account.to_account_info()),
+        };
+
+        let fleet_holder = NftHolder{
+            token:self.fleet_vault.to_account_info(),
+            owner:self.fleet.to_account_info(),
+            token_record:self.fleet_vault_token_record.as_ref().map(|account| account.to_account_info()),
+        };
+
+        (owner_holder,fleet_holder)
+    }
+}
@@ -1,9 +1,10 @@
-use anchor_lang::{prelude::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+    associated_token::AssociatedToken, token::spl_token::native_mint, token_2022::Token2022,
+    token_interface::{Mint, TokenAccount, TokenInterface}
 };
 
-use crate::{errors::ErrorCode, state::*,};
+use crate::{constants::*, errors::ErrorCode, state::*, utils::{Escrow, OdometerReading, OraclePrice, RentalPass}};
 
 
 #[derive(Accounts)]
@@ -13,9 +14,11 @@ pub struct RentCar<'info>{
 
     #[account(mut)]
     pub owner:SystemAccount<'info>,
-    pub car_nft_mint:InterfaceAccount<'info,Mint>,
 
-    pub rent_fee_mint:InterfaceAccount<'info,Mint>,
+    /// CHECK: nft mint or mpl-core asset, pinned by the rental_state seeds
+    pub car_nft_mint:UncheckedAccount<'info>,
+
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
 
     #[account(
         mut,
@@ -31,7 +34,21 @@ pub struct RentCar<'info>{
         associated_token::mint = rent_fee_mint,
         associated_token::authority = rental_state,
     )]
-    pub rent_vault:InterfaceAccount<'info,TokenAccount>,
+    pub rent_vault:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Account<'info,ReferrerStats>>,
 
     #[account(
         init_if_needed,
@@ -39,67 +56,206 @@ pub struct RentCar<'info>{
         associated_token::mint = rent_fee_mint,
         associated_token::authority = renter,
     )]
-    pub renter_ata:InterfaceAccount<'info,TokenAccount>,
+    pub renter_ata:Option<InterfaceAccount<'info,TokenAccount>>,
+
+    //registered oracle for the payment mint, only read for reference priced listings
+    #[account(
+        seeds = [b"price_feed", price_feed.mint.as_ref()],
+        bump = price_feed.price_feed_bump,
+    )]
+    pub price_feed:Option<Box<Account<'info,PriceFeed>>>,
+
+    /// CHECK: oracle price account, checked against the price_feed source
+    pub price_update:Option<UncheckedAccount<'info>>,
+
+    //renter's track record, only needed to claim a deposit tier
+    #[account(
+        seeds = [b"profile", renter.key().as_ref()],
+        bump = renter_profile.profile_bump,
+    )]
+    pub renter_profile:Option<Box<Account<'info,UserProfile>>>,
+
+    //issuer registry and the renter's attestation, only needed when the listing requires claims
+    #[account(
+        seeds = [b"issuer", attestation_issuer.issuer.as_ref()],
+        bump = attestation_issuer.issuer_bump,
+    )]
+    pub attestation_issuer:Option<Box<Account<'info,AttestationIssuer>>>,
+
+    #[account(
+        seeds = [b"attestation", attestation.issuer.as_ref(), renter.key().as_ref()],
+        bump = attestation.attestation_bump,
+    )]
+    pub attestation:Option<Box<Account<'info,Attestation>>>,
+
+    /// CHECK: renter's protocol ban entry, has to be empty
+    #[account(
+        seeds = [b"ban", renter.key().as_ref()],
+        bump
+    )]
+    pub ban_entry:UncheckedAccount<'info>,
+
+    //owner's renter lists, only needed when the listing references them
+    pub allowlist:Option<Box<Account<'info,RenterList>>>,
+    pub blocklist:Option<Box<Account<'info,RenterList>>>,
+
+    //created here as the renter's non-transferable rental pass
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:SystemAccount<'info>,
+
+    /// CHECK: renter's rental pass account, derivation checked by the associated token program
+    #[account(mut)]
+    pub renter_pass:UncheckedAccount<'info>,
+
+    /// CHECK: instructions sysvar, only needed for the start odometer reading of mileage billed listings
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions:Option<UncheckedAccount<'info>>,
 
     pub system_program:Program<'info,System>,
     pub associated_token_program:Program<'info,AssociatedToken>,
     pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
     pub clock: Sysvar<'info,Clock>
 }
 
 impl<'info> RentCar<'info>{
     
-  pub fn rent_car(&mut self,rental_duration:i64)->Result<()>{
+  pub fn rent_car(&mut self,rental_duration:i64,bumps:RentCarBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
     require!(self.rental_state.listed,ErrorCode::CarNotListed);
     require!(!self.rental_state.rented,ErrorCode::RentalPeriodNotEnd);
+    BanEntry::require_not_banned(&self.ban_entry)?;
+
+    Attestation::require_claims(
+      self.rental_state.required_claims,
+      self.attestation_issuer.as_deref().map(|issuer| &**issuer),
+      self.attestation.as_deref().map(|attestation| &**attestation),
+      self.clock.unix_timestamp,
+    )?;
+    RenterList::require_renter(self.rental_state.allowlist,self.allowlist.as_deref().map(|list| (list.key(),&**list)),RenterListKind::Allow,&self.rental_state.owner,&self.renter.key())?;
+    RenterList::require_renter(self.rental_state.blocklist,self.blocklist.as_deref().map(|list| (list.key(),&**list)),RenterListKind::Block,&self.rental_state.owner,&self.renter.key())?;
+
+    self.transfer_rent_fee(remaining_accounts)?;    
 
-    self.transfer_rent_fee()?;    
+    self.rental_state.referrer = match &mut self.referrer_stats {
+        Some(referrer_stats) => {
+            require!(referrer_stats.referrer != self.renter.key(),ErrorCode::SelfReferral);
+            require!(referrer_stats.approved,ErrorCode::ReferrerNotApproved);
+            referrer_stats.rentals_referred = referrer_stats.rentals_referred.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+            Some(referrer_stats.referrer)
+        },
+        None => None,
+    };
 
     self.rental_state.rental_duration = Some(rental_duration);
     self.rental_state.renter = Some(self.renter.key());
     self.rental_state.rented = true;
     self.rental_state.rental_start_time = Some(self.clock.unix_timestamp);
+    self.rental_state.start_odometer = self.read_odometer()?;
+
+    self.issue_pass(bumps.rental_pass_mint)?;
 
     Ok(())
   }
 
-  pub fn transfer_rent_fee(&mut  self)->Result<()>{
+  pub fn read_odometer(&self)->Result<Option<u64>>{
 
-   let total_fee: u64 = self.rental_state.rent_fee.checked_add(self.rental_state.deposit_amount).ok_or(ErrorCode::ValueOverflow)?;
+    let Some(mileage) = &self.rental_state.mileage else {
+      return Ok(None);
+    };
+    let sysvar_instructions = self.sysvar_instructions.as_ref().ok_or(ErrorCode::MissingOdometerReading)?;
 
-   let renter_balance = self.renter_ata.amount;
+    let reading = OdometerReading::load(sysvar_instructions,&mileage.device,&self.rental_state.key(),self.clock.unix_timestamp)?;
 
-   require!(renter_balance>=total_fee,ErrorCode::InsufficientFunds);
+    Ok(Some(reading.odometer_km))
+  }
 
-   //getting rent fee
-    let cpi_program = self.token_program.to_account_info();
+  pub fn issue_pass(&self,mint_bump:u8)->Result<()>{
 
-    let cpi_accounts = TransferChecked{
-        from:self.renter_ata.to_account_info(),
-        to:self.rent_vault.to_account_info(),
-        mint:self.rent_fee_mint.to_account_info(),
-        authority:self.renter.to_account_info()
-    };
+    let start = self.clock.unix_timestamp;
+    let end = start.checked_add(self.rental_state.rental_duration.unwrap_or_default()).ok_or(ErrorCode::ValueOverflow)?;
 
-    let ctx = CpiContext::new(cpi_program.clone(),cpi_accounts);
+    let seeds = self.rental_state.signer_seeds();
+    let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
 
-    transfer_checked(ctx, self.rental_state.rent_fee,self.rent_fee_mint.decimals)?;
+    RentalPass::new(
+      self.token_2022_program.to_account_info(),
+      self.rental_pass_mint.to_account_info(),
+      self.renter_pass.to_account_info(),
+      self.renter.to_account_info(),
+      self.rental_state.to_account_info(),
+      self.system_program.to_account_info(),
+      self.associated_token_program.to_account_info(),
+    ).issue(
+      &self.rental_state.car_nft_mint,
+      start,
+      end,
+      mint_bump,
+      &[&seeds[..]],
+    )
+  }
 
-    //getting deposit fee
-    let cpi_accounts = TransferChecked{
-        from:self.renter_ata.to_account_info(),
-        to:self.rent_vault.to_account_info(),
-        mint:self.rent_fee_mint.to_account_info(),
-        authority:self.renter.to_account_info()
-    };
+  pub fn transfer_rent_fee(&mut  self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
 
-    let ctx = CpiContext::new(cpi_program,cpi_accounts);
+   //the renter's pick of mint is what end_rental settles in
+   if self.rental_state.payment_kind == PaymentKind::Token {
+     self.rental_state.payment_mint = self.rent_fee_mint.as_ref().map(|mint| mint.key());
+   }
 
-    transfer_checked(ctx, self.rental_state.deposit_amount,self.rent_fee_mint.decimals)?;
+   let escrow = Escrow::for_listing(&self.rental_state,self.token_program.to_account_info(),self.system_program.to_account_info(),self.rent_fee_mint.as_ref(),self.rent_vault.as_ref().map(|vault| vault.to_account_info()),self.sol_vault.as_ref().map(|vault| vault.to_account_info()),remaining_accounts)?;
 
-    Ok(())
+   let (source,renter_balance) = match self.rental_state.payment_kind {
+     PaymentKind::Token => {
+       let renter_ata = self.renter_ata.as_ref().ok_or(ErrorCode::MissingPaymentAccounts)?;
+       (renter_ata.to_account_info(),renter_ata.amount)
+     },
+     PaymentKind::Lamports => (self.renter.to_account_info(),self.renter.lamports()),
+   };
+
+   let (rent_fee,deposit_amount) = self.quote()?;
+   let deposit_amount = self.rental_state.tiered_deposit(deposit_amount,self.renter_profile.as_deref().map(|profile| &**profile)).ok_or(ErrorCode::ValueOverflow)?;
+
+   let (escrowed_rent,escrowed_deposit) = escrow.collect_rental(&self.rental_state,(rent_fee,deposit_amount),source,self.renter.to_account_info(),renter_balance)?;
+
+   self.rental_state.escrowed_rent = escrowed_rent;
+   self.rental_state.escrowed_deposit = escrowed_deposit;
+
+   Ok(())
   }
 
-}
+  //token listings charge the listed price for the chosen mint, reference listings convert at the oracle price and lamports use the native mint's feed
+  pub fn quote(&self)->Result<(u64,u64)>{
+
+    if self.rental_state.denomination == PriceDenomination::Token {
+      return match (self.rental_state.payment_kind,&self.rent_fee_mint) {
+        (PaymentKind::Token,Some(rent_fee_mint)) => self.rental_state.price_in(&rent_fee_mint.key()).ok_or(ErrorCode::InvalidPaymentMint.into()),
+        _ => Ok((self.rental_state.rent_fee,self.rental_state.deposit_amount)),
+      };
+    }
 
+    let (Some(price_feed),Some(price_update)) = (&self.price_feed,&self.price_update) else {
+      return err!(ErrorCode::InvalidPriceFeed);
+    };
+
+    let (payment_mint,decimals) = match self.rental_state.payment_kind {
+      PaymentKind::Token => {
+        let rent_fee_mint = self.rent_fee_mint.as_ref().ok_or(ErrorCode::MissingPaymentAccounts)?;
+        (rent_fee_mint.key(),rent_fee_mint.decimals)
+      },
+      PaymentKind::Lamports => (native_mint::ID,LAMPORT_DECIMALS),
+    };
+    require_keys_eq!(price_feed.mint,payment_mint,ErrorCode::InvalidPriceFeed);
+
+    let price = OraclePrice::load(price_feed,price_update)?;
+    price.check(price_feed,self.clock.unix_timestamp)?;
+
+    Ok((
+      price.to_token_amount(self.rental_state.rent_fee,decimals)?,
+      price.to_token_amount(self.rental_state.deposit_amount,decimals)?,
+    ))
+  }
+}
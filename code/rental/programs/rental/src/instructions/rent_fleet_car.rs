@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken, token_2022::Token2022, token_interface::{Mint, TokenAccount, TokenInterface}
+};
+
+use crate::{constants::*, errors::ErrorCode, state::*, utils::{fund_sol_vault, Escrow, RentalPass}};
+
+#[derive(Accounts)]
+pub struct RentFleetCar<'info>{
+    #[account(mut)]
+    pub renter:Signer<'info>,
+
+    pub owner:SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fleet", owner.key().as_ref(), fleet.collection_mint.as_ref()],
+        bump = fleet.fleet_bump,
+        has_one = owner,
+        constraint = fleet.rent_fee_mint == rent_fee_mint.as_ref().map(|mint| mint.key()) @ ErrorCode::InvalidPaymentMint,
+    )]
+    pub fleet:Box<Account<'info,FleetPool>>,
+
+    /// CHECK: one of the fleet's available units, checked in the handler
+    pub car_nft_mint:UncheckedAccount<'info>,
+
+    pub rent_fee_mint:Option<Box<InterfaceAccount<'info,Mint>>>,
+
+    //per-unit escrow record, paid for by the renter and closed back to them on return
+    #[account(
+        init,
+        payer = renter,
+        space = DISCRIMINATOR + RentalState::INIT_SPACE,
+        seeds = [b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub rental_state:Box<Account<'info,RentalState>>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = rental_state,
+    )]
+    pub rent_vault:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault", rental_state.key().as_ref()],
+        bump
+    )]
+    pub sol_vault:Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.referrer_bump,
+    )]
+    pub referrer_stats:Option<Box<Account<'info,ReferrerStats>>>,
+
+    #[account(
+        init_if_needed,
+        payer = renter,
+        associated_token::mint = rent_fee_mint,
+        associated_token::authority = renter,
+    )]
+    pub renter_ata:Option<Box<InterfaceAccount<'info,TokenAccount>>>,
+
+    //issuer registry and the renter's attestation, only needed when the listing requires claims
+    #[account(
+        seeds = [b"issuer", attestation_issuer.issuer.as_ref()],
+        bump = attestation_issuer.issuer_bump,
+    )]
+    pub attestation_issuer:Option<Box<Account<'info,AttestationIssuer>>>,
+
+    #[account(
+        seeds = [b"attestation", attestation.issuer.as_ref(), renter.key().as_ref()],
+        bump = attestation.attestation_bump,
+    )]
+    pub attestation:Option<Box<Account<'info,Attestation>>>,
+
+    /// CHECK: renter's protocol ban entry, has to be empty
+    #[account(
+        seeds = [b"ban", renter.key().as_ref()],
+        bump
+    )]
+    pub ban_entry:UncheckedAccount<'info>,
+
+    //owner's renter lists, only needed when the listing references them
+    pub allowlist:Option<Box<Account<'info,RenterList>>>,
+    pub blocklist:Option<Box<Account<'info,RenterList>>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental_pass", rental_state.key().as_ref()],
+        bump
+    )]
+    pub rental_pass_mint:SystemAccount<'info>,
+
+    /// CHECK: renter's rental pass account, derivation checked by the associated token program
+    #[account(mut)]
+    pub renter_pass:UncheckedAccount<'info>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub token_2022_program:Program<'info,Token2022>,
+}
+
+impl<'info> RentFleetCar<'info>{
+
+    pub fn rent_fleet_car(&mut self,rental_duration:i64,bumps:RentFleetCarBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        BanEntry::require_not_banned(&self.ban_entry)?;
+
+        Attestation::require_claims(
+            self.fleet.required_claims,
+            self.attestation_issuer.as_deref().map(|issuer| &**issuer),
+            self.attestation.as_deref().map(|attestation| &**attestation),
+            Clock::get()?.unix_timestamp,
+        )?;
+        RenterList::require_renter(self.fleet.allowlist,self.allowlist.as_deref().map(|list| (list.key(),&**list)),RenterListKind::Allow,&self.fleet.owner,&self.renter.key())?;
+        RenterList::require_renter(self.fleet.blocklist,self.blocklist.as_deref().map(|list| (list.key(),&**list)),RenterListKind::Block,&self.fleet.owner,&self.renter.key())?;
+
+        //any available unit will do, clients pass the first one
+        let car_nft_mint = self.car_nft_mint.key();
+        let position = self.fleet.available.iter().position(|unit| *unit == car_nft_mint).ok_or(ErrorCode::FleetUnitUnavailable)?;
+        self.fleet.available.swap_remove(position);
+        self.fleet.rented_units = self.fleet.rented_units.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+
+        let referrer = match &mut self.referrer_stats {
+            Some(referrer_stats) => {
+                require!(referrer_stats.referrer != self.renter.key(),ErrorCode::SelfReferral);
+                require!(referrer_stats.approved,ErrorCode::ReferrerNotApproved);
+                referrer_stats.rentals_referred = referrer_stats.rentals_referred.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+                Some(referrer_stats.referrer)
+            },
+            None => None,
+        };
+
+        self.rental_state.set_inner(RentalState{
+            renter:Some(self.renter.key()),
+            rental_duration:Some(rental_duration),
+            rental_start_time:Some(Clock::get()?.unix_timestamp),
+            rented:true,
+            referrer,
+            fleet:Some(self.fleet.key()),
+            payment_mint:self.rent_fee_mint.as_ref().map(|mint| mint.key()),
+            ..RentalState::new_listing(
+                self.owner.key(),
+                car_nft_mint,
+                self.fleet.rent_fee_mint,
+                self.fleet.rent_fee,
+                self.fleet.deposit_amount,
+                self.fleet.unit_options(),
+                (bumps.rental_state,bumps.sol_vault),
+            )
+        });
+
+        //the renter fronts the vault's rent-exempt minimum and gets it back when the unit is returned
+        if self.rental_state.payment_kind == PaymentKind::Lamports {
+            fund_sol_vault(self.system_program.to_account_info(),self.renter.to_account_info(),self.sol_vault.as_ref().map(|vault| vault.to_account_info()))?;
+        }
+
+        self.transfer_rent_fee(remaining_accounts)?;
+
+        self.issue_pass(bumps.rental_pass_mint)?;
+
+        Ok(())
+    }
+
+    pub fn issue_pass(&self,mint_bump:u8)->Result<()>{
+
+        let start = self.rental_state.rental_start_time.unwrap_or_default();
+        let end = start.checked_add(self.rental_state.rental_duration.unwrap_or_default()).ok_or(ErrorCode::ValueOverflow)?;
+
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        RentalPass::new(
+            self.token_2022_program.to_account_info(),
+            self.rental_pass_mint.to_account_info(),
+            self.renter_pass.to_account_info(),
+            self.renter.to_account_info(),
+            self.rental_state.to_account_info(),
+            self.system_program.to_account_info(),
+            self.associated_token_program.to_account_info(),
+        ).issue(
+            &self.rental_state.car_nft_mint,
+            start,
+            end,
+            mint_bump,
+            &[&seeds[..]],
+        )
+    }
+
+    pub fn transfer_rent_fee(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        let escrow = Escrow::for_listing(&self.rental_state,self.token_program.to_account_info(),self.system_program.to_account_info(),self.rent_fee_mint.as_deref(),self.rent_vault.as_ref().map(|vault| vault.to_account_info()),self.sol_vault.as_ref().map(|vault| vault.to_account_info()),remaining_accounts)?;
+
+        let (source,renter_balance) = match self.rental_state.payment_kind {
+            PaymentKind::Token => {
+                let renter_ata = self.renter_ata.as_ref().ok_or(ErrorCode::MissingPaymentAccounts)?;
+                (renter_ata.to_account_info(),renter_ata.amount)
+            },
+            PaymentKind::Lamports => (self.renter.to_account_info(),self.renter.lamports()),
+        };
+
+        let (escrowed_rent,escrowed_deposit) = escrow.collect_rental(&self.rental_state,(self.rental_state.rent_fee,self.rental_state.deposit_amount),source,self.renter.to_account_info(),renter_balance)?;
+
+        self.rental_state.escrowed_rent = escrowed_rent;
+        self.rental_state.escrowed_deposit = escrowed_deposit;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::{prelude::*};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RevokeOperator<'info>{
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"operator", owner.key().as_ref(), operator_grant.operator.as_ref()],
+        bump = operator_grant.grant_bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub operator_grant:Account<'info,OperatorGrant>,
+}
+
+impl<'info> RevokeOperator<'info>{
+    pub fn revoke_operator(&mut self)->Result<()>{
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct SetAttestationIssuer<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    /// CHECK: issuer wallet, matched against the proposal
+    pub issuer:UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = DISCRIMINATOR + AttestationIssuer::INIT_SPACE,
+        seeds = [b"issuer", issuer.key().as_ref()],
+        bump
+    )]
+    pub attestation_issuer:Account<'info,AttestationIssuer>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> SetAttestationIssuer<'info>{
+    pub fn set_attestation_issuer(&mut self,bumps:SetAttestationIssuerBumps)->Result<()>{
+
+        let ProposalAction::SetAttestationIssuer { issuer, active } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(issuer == self.issuer.key(),ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        self.attestation_issuer.set_inner(AttestationIssuer{
+            version:AttestationIssuer::VERSION,
+            issuer,
+            active,
+            issuer_bump:bumps.attestation_issuer,
+        });
+
+        Ok(())
+    }
+}
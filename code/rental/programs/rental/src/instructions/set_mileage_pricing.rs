@@ -0,0 +1,58 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{token::Token, token_2022::Token2022, token_interface::Mint};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct SetMileagePricing<'info>{
+    //the owner, or an operator they authorised to update prices
+    pub authority:Signer<'info>,
+
+    pub owner:SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Account<'info,OperatorGrant>>,
+
+    /// CHECK: mint of the car nft, or a core asset or compressed leaf id, which the handler rejects
+    pub car_nft_mint:UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rental", car_nft_mint.key().as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one = owner,
+    )]
+    pub rental_state:Account<'info,RentalState>,
+}
+
+impl<'info> SetMileagePricing<'info>{
+
+    //registers the car's telematics device and per km price, None goes back to flat rentals
+    pub fn set_mileage_pricing(&mut self,mileage:Option<MileagePricing>)->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref(),OPERATOR_UPDATE_PRICES)?;
+        require!(!self.rental_state.rented,ErrorCode::CarAlreadyRented);
+
+        //fee_per_km is charged from the deposit, so it has to be in the deposit's units
+        if mileage.is_some() {
+            self.require_nft_listing()?;
+            require!(self.rental_state.denomination == PriceDenomination::Token && self.rental_state.accepted_payments.is_empty(),ErrorCode::MileageUnsupported);
+        }
+
+        self.rental_state.mileage = mileage;
+
+        Ok(())
+    }
+
+    //end_rental is the only path that bills mileage, core, compressed and fleet listings settle elsewhere
+    pub fn require_nft_listing(&self)->Result<()>{
+        let car = self.car_nft_mint.to_account_info();
+        let is_mint = (car.owner == &Token::id() || car.owner == &Token2022::id()) && Mint::try_deserialize(&mut &car.try_borrow_data()?[..]).is_ok();
+
+        require!(is_mint && self.rental_state.fleet.is_none(),ErrorCode::MileageUnsupported);
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*, constants::*};
+
+#[derive(Accounts)]
+pub struct SetOperator<'info>{
+    #[account(mut)]
+    pub owner:Signer<'info>,
+
+    /// CHECK: operator key being authorised, never read
+    pub operator:UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = DISCRIMINATOR + OperatorGrant::INIT_SPACE,
+        seeds = [b"operator", owner.key().as_ref(), operator.key().as_ref()],
+        bump
+    )]
+    pub operator_grant:Account<'info,OperatorGrant>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> SetOperator<'info>{
+
+    //replaces whatever the operator could do before
+    pub fn set_operator(&mut self,permissions:u8,bumps:SetOperatorBumps)->Result<()>{
+
+        self.operator_grant.set_inner(OperatorGrant{
+            version:OperatorGrant::VERSION,
+            owner:self.owner.key(),
+            operator:self.operator.key(),
+            permissions,
+            grant_bump:bumps.operator_grant,
+        });
+
+        Ok(())
+    }
+}
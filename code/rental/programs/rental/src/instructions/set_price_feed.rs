@@ -0,0 +1,67 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    /// CHECK: payment mint the feed prices, the native mint for lamports, matched against the proposal
+    pub mint:UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = DISCRIMINATOR + PriceFeed::INIT_SPACE,
+        seeds = [b"price_feed", mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed:Account<'info,PriceFeed>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> SetPriceFeed<'info>{
+    pub fn set_price_feed(&mut self,bumps:SetPriceFeedBumps)->Result<()>{
+
+        let ProposalAction::SetPriceFeed { mint, source, max_staleness, max_confidence_bps } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(mint == self.mint.key(),ErrorCode::ProposalActionMismatch);
+        require!(max_staleness > 0 && max_confidence_bps as u64 <= BPS_DENOMINATOR,ErrorCode::InvalidPriceFeed);
+        self.proposal.execute(&self.multisig)?;
+
+        self.price_feed.set_inner(PriceFeed{
+            version:PriceFeed::VERSION,
+            mint,
+            source,
+            max_staleness,
+            max_confidence_bps,
+            price_feed_bump:bumps.price_feed,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken, token::Token,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct StakeShares<'info>{
+    #[account(mut)]
+    pub holder:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fraction", fraction_pool.rental_state.as_ref()],
+        bump = fraction_pool.pool_bump,
+    )]
+    pub fraction_pool:Box<Account<'info,FractionPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = DISCRIMINATOR + SharePosition::INIT_SPACE,
+        seeds = [b"share_position", fraction_pool.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub position:Box<Account<'info,SharePosition>>,
+
+    #[account(address = fraction_pool.share_mint)]
+    pub share_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = share_token_program,
+    )]
+    pub holder_shares:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = share_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = share_token_program,
+    )]
+    pub share_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(address = fraction_pool.reward_mint)]
+    pub reward_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_rewards:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub share_token_program:Program<'info,Token>,
+}
+
+impl<'info> StakeShares<'info>{
+
+    //only staked shares can claim rent, staking settles what the position earned so far first
+    pub fn stake_shares(&mut self,amount:u64,bumps:StakeSharesBumps,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        if self.position.version == 0 {
+            self.position.set_inner(SharePosition{
+                version:SharePosition::VERSION,
+                pool:self.fraction_pool.key(),
+                holder:self.holder.key(),
+                shares:0,
+                reward_debt:0,
+                position_bump:bumps.position,
+            });
+        }
+
+        let pending = self.fraction_pool.settle(&mut self.position,self.reward_vault.amount)?;
+        FractionPool::pay_rewards(&self.fraction_pool,self.token_program.to_account_info(),self.reward_vault.to_account_info(),&self.reward_mint,self.holder_rewards.to_account_info(),pending,remaining_accounts)?;
+
+        transfer_checked(CpiContext::new(self.share_token_program.to_account_info(),TransferChecked{
+            from:self.holder_shares.to_account_info(),
+            to:self.share_vault.to_account_info(),
+            mint:self.share_mint.to_account_info(),
+            authority:self.holder.to_account_info(),
+        }),amount,self.share_mint.decimals)?;
+
+        self.position.shares = self.position.shares.checked_add(amount).ok_or(ErrorCode::ValueOverflow)?;
+        self.fraction_pool.staked_shares = self.fraction_pool.staked_shares.checked_add(amount).ok_or(ErrorCode::ValueOverflow)?;
+        self.position.reward_debt = self.position.accrued(&self.fraction_pool)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct UnbanWallet<'info>{
+    #[account(mut)]
+    pub payer:Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    /// CHECK: banned wallet, matched against the proposal
+    pub wallet:UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"ban", wallet.key().as_ref()],
+        bump = ban_entry.ban_bump,
+        close = payer,
+    )]
+    pub ban_entry:Account<'info,BanEntry>,
+}
+
+impl<'info> UnbanWallet<'info>{
+    pub fn unban_wallet(&mut self)->Result<()>{
+
+        let ProposalAction::UnbanWallet { wallet } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(wallet == self.wallet.key(),ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)
+    }
+}
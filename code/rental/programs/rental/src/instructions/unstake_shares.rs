@@ -0,0 +1,101 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken, token::Token,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}
+};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct UnstakeShares<'info>{
+    #[account(mut)]
+    pub holder:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"fraction", fraction_pool.rental_state.as_ref()],
+        bump = fraction_pool.pool_bump,
+    )]
+    pub fraction_pool:Box<Account<'info,FractionPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"share_position", fraction_pool.key().as_ref(), holder.key().as_ref()],
+        bump = position.position_bump,
+        has_one = holder,
+    )]
+    pub position:Box<Account<'info,SharePosition>>,
+
+    #[account(address = fraction_pool.share_mint)]
+    pub share_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = share_token_program,
+    )]
+    pub holder_shares:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = share_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = share_token_program,
+    )]
+    pub share_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(address = fraction_pool.reward_mint)]
+    pub reward_mint:Box<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = fraction_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_rewards:Box<InterfaceAccount<'info,TokenAccount>>,
+
+    pub system_program:Program<'info,System>,
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+    pub share_token_program:Program<'info,Token>,
+}
+
+impl<'info> UnstakeShares<'info>{
+
+    //hands shares back after settling, unstaked shares stop earning but stay freely transferable
+    pub fn unstake_shares(&mut self,amount:u64,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        require!(amount <= self.position.shares,ErrorCode::InsufficientShares);
+
+        let pending = self.fraction_pool.settle(&mut self.position,self.reward_vault.amount)?;
+        FractionPool::pay_rewards(&self.fraction_pool,self.token_program.to_account_info(),self.reward_vault.to_account_info(),&self.reward_mint,self.holder_rewards.to_account_info(),pending,remaining_accounts)?;
+
+        let seeds = self.fraction_pool.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        transfer_checked(CpiContext::new_with_signer(self.share_token_program.to_account_info(),TransferChecked{
+            from:self.share_vault.to_account_info(),
+            to:self.holder_shares.to_account_info(),
+            mint:self.share_mint.to_account_info(),
+            authority:self.fraction_pool.to_account_info(),
+        },&[&seeds[..]]),amount,self.share_mint.decimals)?;
+
+        self.position.shares -= amount;
+        self.fraction_pool.staked_shares = self.fraction_pool.staked_shares.checked_sub(amount).ok_or(ErrorCode::ValueOverflow)?;
+        self.position.reward_debt = self.position.accrued(&self.fraction_pool)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token_interface::Mint;
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct UpdateListingPrice<'info>{
+    //the owner, or an operator they authorised to update prices
+    pub authority:Signer<'info>,
+
+    pub owner:SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"operator", owner.key().as_ref(), authority.key().as_ref()],
+        bump = operator_grant.grant_bump,
+    )]
+    pub operator_grant:Option<Account<'info,OperatorGrant>>,
+
+    #[account(
+        mut,
+        seeds = [b"rental", rental_state.car_nft_mint.as_ref(), owner.key().as_ref()],
+        bump = rental_state.rental_bump,
+        has_one = owner,
+    )]
+    pub rental_state:Account<'info,RentalState>,
+
+    //the mint the price is in, only needed by token listings that have none on record yet
+    pub rent_fee_mint:Option<InterfaceAccount<'info,Mint>>,
+}
+
+impl<'info> UpdateListingPrice<'info>{
+
+    //reprices the base rent and deposit between rentals, the current renter keeps the price they paid
+    pub fn update_listing_price(&mut self,rent_fee:u64,deposit_amount:u64)->Result<()>{
+
+        OperatorGrant::require_permission(&self.owner.key(),&self.authority.key(),self.operator_grant.as_deref(),OPERATOR_UPDATE_PRICES)?;
+        require!(!self.rental_state.rented,ErrorCode::CarAlreadyRented);
+        require!(self.rental_state.fleet.is_none(),ErrorCode::FleetRental);
+
+        //listings migrated from before payment mints were recorded pin theirs with their next price
+        if self.rental_state.payment_kind == PaymentKind::Token {
+            match (self.rental_state.rent_fee_mint,&self.rent_fee_mint) {
+                (None,Some(mint)) => self.rental_state.rent_fee_mint = Some(mint.key()),
+                (None,None) => return err!(ErrorCode::MissingPaymentAccounts),
+                (Some(pinned),Some(mint)) => require_keys_eq!(pinned,mint.key(),ErrorCode::InvalidPaymentMint),
+                (Some(_),None) => {},
+            }
+        }
+
+        self.rental_state.rent_fee = rent_fee;
+        self.rental_state.deposit_amount = deposit_amount;
+
+        Ok(())
+    }
+}
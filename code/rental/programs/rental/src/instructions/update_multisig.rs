@@ -0,0 +1,38 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct UpdateMultisig<'info>{
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+}
+
+impl<'info> UpdateMultisig<'info>{
+    pub fn update_multisig(&mut self)->Result<()>{
+
+        let ProposalAction::UpdateMultisig { members, threshold } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        self.proposal.execute(&self.multisig)?;
+
+        Multisig::validate_members(&members,threshold)?;
+
+        self.multisig.members = members;
+        self.multisig.threshold = threshold;
+
+        Ok(())
+    }
+}
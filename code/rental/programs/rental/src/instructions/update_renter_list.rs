@@ -0,0 +1,37 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct UpdateRenterList<'info>{
+    pub owner:Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"renter_list", owner.key().as_ref(), renter_list.list_id.to_le_bytes().as_ref()],
+        bump = renter_list.list_bump,
+        has_one = owner,
+    )]
+    pub renter_list:Account<'info,RenterList>,
+}
+
+impl<'info> UpdateRenterList<'info>{
+
+    //every listing referencing the list sees the change on its next rental
+    pub fn update_renter_list(&mut self,add:Vec<Pubkey>,remove:Vec<Pubkey>)->Result<()>{
+
+        let renters = &mut self.renter_list.renters;
+
+        renters.retain(|renter| !remove.contains(renter));
+
+        for renter in add {
+            if !renters.contains(&renter) {
+                renters.push(renter);
+            }
+        }
+
+        require!(renters.len() <= MAX_LIST_RENTERS,ErrorCode::RenterListFull);
+
+        Ok(())
+    }
+}
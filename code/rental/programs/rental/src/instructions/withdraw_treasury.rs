@@ -0,0 +1,84 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked}
+};
+
+use crate::{state::*,errors::ErrorCode, utils::transfer_checked_with_hook};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info>{
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    pub mint:InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config,
+    )]
+    pub treasury:InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub destination:InterfaceAccount<'info,TokenAccount>,
+
+    pub associated_token_program:Program<'info,AssociatedToken>,
+    pub token_program:Interface<'info,TokenInterface>,
+}
+
+impl<'info> WithdrawTreasury<'info>{
+    pub fn withdraw_treasury(&mut self,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+
+        let ProposalAction::WithdrawTreasury { mint, destination, amount } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(mint == self.mint.key() && destination == self.destination.key(),ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        require!(self.treasury.amount >= amount,ErrorCode::InsufficientFunds);
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked{
+            from:self.treasury.to_account_info(),
+            to:self.destination.to_account_info(),
+            mint:self.mint.to_account_info(),
+            authority:self.config.to_account_info()
+        };
+
+        let seeds = [
+            b"config".as_ref(),
+            &[self.config.config_bump]
+        ];
+
+        let signer_seed = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(cpi_program,cpi_accounts,signer_seed).with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(ctx, amount,self.mint.decimals)?;
+
+        Ok(())
+    }
+}
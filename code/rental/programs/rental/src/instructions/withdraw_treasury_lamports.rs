@@ -0,0 +1,54 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryLamports<'info>{
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.multisig_bump,
+    )]
+    pub multisig:Account<'info,Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.proposal_bump,
+        has_one = multisig,
+    )]
+    pub proposal:Account<'info,Proposal>,
+
+    //lamport protocol fees are held on the config account above its rent-exempt reserve
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.config_bump,
+        constraint = config.admin == multisig.key() @ ErrorCode::InvalidAdmin,
+    )]
+    pub config:Account<'info,Config>,
+
+    #[account(mut)]
+    pub destination:SystemAccount<'info>,
+}
+
+impl<'info> WithdrawTreasuryLamports<'info>{
+    pub fn withdraw_treasury_lamports(&mut self)->Result<()>{
+
+        let ProposalAction::WithdrawTreasuryLamports { destination, amount } = self.proposal.action.clone() else {
+            return err!(ErrorCode::ProposalActionMismatch);
+        };
+        require!(destination == self.destination.key(),ErrorCode::ProposalActionMismatch);
+        self.proposal.execute(&self.multisig)?;
+
+        let config_info = self.config.to_account_info();
+        let reserve = Rent::get()?.minimum_balance(config_info.data_len());
+        let available = config_info.lamports().saturating_sub(reserve);
+
+        require!(available >= amount,ErrorCode::InsufficientFunds);
+
+        config_info.sub_lamports(amount)?;
+        self.destination.add_lamports(amount)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::{prelude::*};
+
+use crate::{state::*,errors::ErrorCode, constants::*};
+
+#[derive(Accounts)]
+pub struct WriteAttestation<'info>{
+    #[account(mut)]
+    pub issuer:Signer<'info>,
+
+    #[account(
+        seeds = [b"issuer", issuer.key().as_ref()],
+        bump = attestation_issuer.issuer_bump,
+        constraint = attestation_issuer.active @ ErrorCode::InactiveIssuer,
+    )]
+    pub attestation_issuer:Account<'info,AttestationIssuer>,
+
+    /// CHECK: wallet being attested, never read
+    pub subject:UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = DISCRIMINATOR + Attestation::INIT_SPACE,
+        seeds = [b"attestation", issuer.key().as_ref(), subject.key().as_ref()],
+        bump
+    )]
+    pub attestation:Account<'info,Attestation>,
+
+    pub system_program:Program<'info,System>,
+}
+
+impl<'info> WriteAttestation<'info>{
+
+    //overwrites any earlier claims, issuers revoke by writing no claims or a past expiry
+    pub fn write_attestation(&mut self,claims:u32,expires_at:i64,bumps:WriteAttestationBumps)->Result<()>{
+
+        self.attestation.set_inner(Attestation{
+            version:Attestation::VERSION,
+            issuer:self.issuer.key(),
+            subject:self.subject.key(),
+            claims,
+            expires_at,
+            attestation_bump:bumps.attestation,
+        });
+
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+//only for anchor's generated IdlResizeAccount, which #[program] expands at the crate root with the deprecated
+//AccountInfo::realloc, so no narrower scope reaches it. this crate's own code builds clean without it
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
 declare_id!("5NKJtqgRw4dcLSX7nqNmLAbHKuodRctZ9LzLsHcZnbfZ");
@@ -6,40 +10,254 @@ pub mod errors;
 pub mod state;
 pub mod constants;
 pub mod instructions;
+pub mod utils;
 
-pub use errors::*;
+pub use errors::ErrorCode;
 pub use state::*;
 pub use constants::*;
-pub use instructions::{list_car::*,end_rental::*,rent_car::*,emergency_exit::*};
+pub use utils::CompressedLeaf;
+pub use instructions::{list_car::*,end_rental::*,rent_car::*,emergency_exit::*,initialize_config::*,propose_config::*,apply_config::*,cancel_config::*,initialize_multisig::*,create_proposal::*,approve_proposal::*,update_multisig::*,withdraw_treasury::*,migrate_rental_state::*,register_referrer::*,withdraw_treasury_lamports::*,list_core_car::*,end_core_rental::*,core_emergency_exit::*,list_compressed_car::*,end_compressed_rental::*,compressed_emergency_exit::*,create_fleet::*,add_fleet_car::*,remove_fleet_car::*,rent_fleet_car::*,end_fleet_rental::*,set_price_feed::*,open_dispute::*,rate_rental::*,set_attestation_issuer::*,write_attestation::*,create_renter_list::*,update_renter_list::*,ban_wallet::*,unban_wallet::*,set_operator::*,revoke_operator::*,update_listing_price::*,fractionalize_car::*,stake_shares::*,unstake_shares::*,claim_share_rewards::*,set_mileage_pricing::*,approve_referrer::*,redeem_car::*,claim_overdue::*};
 
 #[program]
 pub mod rental {
 
     use super::*;
 
-    pub fn list_car(ctx:Context<ListCar>,rent_fee:u64,deposit_amount:u64)->Result<()>{
-        ctx.accounts.list_car(rent_fee,deposit_amount,ctx.bumps)?;
+    pub fn list_car<'info>(ctx:Context<'_, '_, 'info, 'info, ListCar<'info>>,rent_fee:u64,deposit_amount:u64,options:ListingOptions)->Result<()>{
+        ctx.accounts.list_car(rent_fee,deposit_amount,options,ctx.bumps,ctx.remaining_accounts)?;
 
         Ok(())
     }
 
-    pub fn rent_car(ctx:Context<RentCar>,rental_duration:i64)->Result<()>{
-        ctx.accounts.rent_car(rental_duration)?;
+    pub fn rent_car<'info>(ctx:Context<'_, '_, 'info, 'info, RentCar<'info>>,rental_duration:i64)->Result<()>{
+        ctx.accounts.rent_car(rental_duration,ctx.bumps,ctx.remaining_accounts)?;
         Ok(())
     }
 
-    pub fn end_rental(ctx:Context<EndRental>)->Result<()>{
-        ctx.accounts.end_rental()?;
+    pub fn end_rental<'info>(ctx:Context<'_, '_, 'info, 'info, EndRental<'info>>)->Result<()>{
+        ctx.accounts.end_rental(ctx.bumps,ctx.remaining_accounts)?;
         Ok(())
     }
 
-    pub fn emergency_exit(ctx:Context<EmergencyExit>,renter_payout:u64,owner_payout:u64)->Result<()>{
-        ctx.accounts.exit_payout(renter_payout,owner_payout)?;
+    pub fn emergency_exit<'info>(ctx:Context<'_, '_, 'info, 'info, EmergencyExit<'info>>,renter_payout:u64,owner_payout:u64)->Result<()>{
+        ctx.accounts.exit_payout(ctx.bumps,renter_payout,owner_payout,ctx.remaining_accounts)?;
 
         
         Ok(())
     }
 
+    pub fn initialize_config(ctx:Context<InitializeConfig>,params:ConfigParams)->Result<()>{
+        ctx.accounts.initialize_config(params,ctx.bumps)?;
+        Ok(())
+    }
 
-}
+    pub fn propose_config(ctx:Context<ProposeConfig>)->Result<()>{
+        ctx.accounts.propose_config()?;
+        Ok(())
+    }
+
+    pub fn apply_config(ctx:Context<ApplyConfig>)->Result<()>{
+        ctx.accounts.apply_config()?;
+        Ok(())
+    }
+
+    pub fn cancel_config(ctx:Context<CancelConfig>)->Result<()>{
+        ctx.accounts.cancel_config()?;
+        Ok(())
+    }
+
+    pub fn initialize_multisig(ctx:Context<InitializeMultisig>,members:Vec<Pubkey>,threshold:u8)->Result<()>{
+        ctx.accounts.initialize_multisig(members,threshold,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_proposal(ctx:Context<CreateProposal>,action:ProposalAction)->Result<()>{
+        ctx.accounts.create_proposal(action,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn approve_proposal(ctx:Context<ApproveProposal>)->Result<()>{
+        ctx.accounts.approve_proposal()?;
+        Ok(())
+    }
+
+    pub fn update_multisig(ctx:Context<UpdateMultisig>)->Result<()>{
+        ctx.accounts.update_multisig()?;
+        Ok(())
+    }
+
+    pub fn withdraw_treasury<'info>(ctx:Context<'_, '_, 'info, 'info, WithdrawTreasury<'info>>)->Result<()>{
+        ctx.accounts.withdraw_treasury(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn migrate_rental_state(ctx:Context<MigrateRentalState>)->Result<()>{
+        ctx.accounts.migrate_rental_state()?;
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx:Context<RegisterReferrer>)->Result<()>{
+        ctx.accounts.register_referrer(ctx.bumps)?;
+        Ok(())
+    }
 
+    pub fn withdraw_treasury_lamports(ctx:Context<WithdrawTreasuryLamports>)->Result<()>{
+        ctx.accounts.withdraw_treasury_lamports()?;
+        Ok(())
+    }
+
+    pub fn list_core_car(ctx:Context<ListCoreCar>,rent_fee:u64,deposit_amount:u64,options:ListingOptions)->Result<()>{
+        ctx.accounts.list_core_car(rent_fee,deposit_amount,options,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn end_core_rental<'info>(ctx:Context<'_, '_, 'info, 'info, EndCoreRental<'info>>)->Result<()>{
+        ctx.accounts.end_core_rental(ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn core_emergency_exit<'info>(ctx:Context<'_, '_, 'info, 'info, CoreEmergencyExit<'info>>,renter_payout:u64,owner_payout:u64)->Result<()>{
+        ctx.accounts.core_exit_payout(ctx.bumps,renter_payout,owner_payout,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn list_compressed_car<'info>(ctx:Context<'_, '_, 'info, 'info, ListCompressedCar<'info>>,rent_fee:u64,deposit_amount:u64,options:ListingOptions,leaf:CompressedLeaf)->Result<()>{
+        ctx.accounts.list_compressed_car(rent_fee,deposit_amount,options,leaf,ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn end_compressed_rental<'info>(ctx:Context<'_, '_, 'info, 'info, EndCompressedRental<'info>>,leaf:CompressedLeaf)->Result<()>{
+        ctx.accounts.end_compressed_rental(ctx.bumps,leaf,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn compressed_emergency_exit<'info>(ctx:Context<'_, '_, 'info, 'info, CompressedEmergencyExit<'info>>,renter_payout:u64,owner_payout:u64,leaf:CompressedLeaf)->Result<()>{
+        ctx.accounts.compressed_exit_payout(ctx.bumps,renter_payout,owner_payout,leaf,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn create_fleet(ctx:Context<CreateFleet>,rent_fee:u64,deposit_amount:u64,options:ListingOptions)->Result<()>{
+        ctx.accounts.create_fleet(rent_fee,deposit_amount,options,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn add_fleet_car<'info>(ctx:Context<'_, '_, 'info, 'info, AddFleetCar<'info>>)->Result<()>{
+        ctx.accounts.add_fleet_car(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn remove_fleet_car<'info>(ctx:Context<'_, '_, 'info, 'info, RemoveFleetCar<'info>>)->Result<()>{
+        ctx.accounts.remove_fleet_car(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn rent_fleet_car<'info>(ctx:Context<'_, '_, 'info, 'info, RentFleetCar<'info>>,rental_duration:i64)->Result<()>{
+        ctx.accounts.rent_fleet_car(rental_duration,ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn end_fleet_rental<'info>(ctx:Context<'_, '_, 'info, 'info, EndFleetRental<'info>>)->Result<()>{
+        ctx.accounts.end_fleet_rental(ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn set_price_feed(ctx:Context<SetPriceFeed>)->Result<()>{
+        ctx.accounts.set_price_feed(ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn open_dispute(ctx:Context<OpenDispute>)->Result<()>{
+        ctx.accounts.open_dispute(ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn rate_rental(ctx:Context<RateRental>,score:u8,review_hash:Option<[u8;32]>)->Result<()>{
+        ctx.accounts.rate_rental(score,review_hash)?;
+        Ok(())
+    }
+
+    pub fn set_attestation_issuer(ctx:Context<SetAttestationIssuer>)->Result<()>{
+        ctx.accounts.set_attestation_issuer(ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn write_attestation(ctx:Context<WriteAttestation>,claims:u32,expires_at:i64)->Result<()>{
+        ctx.accounts.write_attestation(claims,expires_at,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_renter_list(ctx:Context<CreateRenterList>,list_id:u16,kind:RenterListKind)->Result<()>{
+        ctx.accounts.create_renter_list(list_id,kind,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn update_renter_list(ctx:Context<UpdateRenterList>,add:Vec<Pubkey>,remove:Vec<Pubkey>)->Result<()>{
+        ctx.accounts.update_renter_list(add,remove)?;
+        Ok(())
+    }
+
+    pub fn ban_wallet(ctx:Context<BanWallet>)->Result<()>{
+        ctx.accounts.ban_wallet(ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn unban_wallet(ctx:Context<UnbanWallet>)->Result<()>{
+        ctx.accounts.unban_wallet()?;
+        Ok(())
+    }
+
+    pub fn set_operator(ctx:Context<SetOperator>,permissions:u8)->Result<()>{
+        ctx.accounts.set_operator(permissions,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn revoke_operator(ctx:Context<RevokeOperator>)->Result<()>{
+        ctx.accounts.revoke_operator()?;
+        Ok(())
+    }
+
+    pub fn update_listing_price(ctx:Context<UpdateListingPrice>,rent_fee:u64,deposit_amount:u64)->Result<()>{
+        ctx.accounts.update_listing_price(rent_fee,deposit_amount)?;
+        Ok(())
+    }
+
+    pub fn fractionalize_car(ctx:Context<FractionalizeCar>,total_shares:u64)->Result<()>{
+        ctx.accounts.fractionalize_car(total_shares,ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn stake_shares<'info>(ctx:Context<'_, '_, 'info, 'info, StakeShares<'info>>,amount:u64)->Result<()>{
+        ctx.accounts.stake_shares(amount,ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn unstake_shares<'info>(ctx:Context<'_, '_, 'info, 'info, UnstakeShares<'info>>,amount:u64)->Result<()>{
+        ctx.accounts.unstake_shares(amount,ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn claim_share_rewards<'info>(ctx:Context<'_, '_, 'info, 'info, ClaimShareRewards<'info>>)->Result<()>{
+        ctx.accounts.claim_share_rewards(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn set_mileage_pricing(ctx:Context<SetMileagePricing>,mileage:Option<MileagePricing>)->Result<()>{
+        ctx.accounts.set_mileage_pricing(mileage)?;
+        Ok(())
+    }
+
+    pub fn approve_referrer(ctx:Context<ApproveReferrer>)->Result<()>{
+        ctx.accounts.approve_referrer()?;
+        Ok(())
+    }
+
+    pub fn redeem_car<'info>(ctx:Context<'_, '_, 'info, 'info, RedeemCar<'info>>)->Result<()>{
+        ctx.accounts.redeem_car(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn claim_overdue<'info>(ctx:Context<'_, '_, 'info, 'info, ClaimOverdue<'info>>)->Result<()>{
+        ctx.accounts.claim_overdue(ctx.bumps,ctx.remaining_accounts)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+//an issuer the multisig trusts to vouch for drivers, deactivating it voids everything it wrote
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationIssuer{
+    pub version:u8,
+    pub issuer:Pubkey,
+    pub active:bool,
+    pub issuer_bump:u8,
+}
+
+impl AttestationIssuer{
+    pub const VERSION:u8 = 1;
+}
+
+//one issuer's claims about one wallet, see the CLAIM_* bits
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation{
+    pub version:u8,
+    pub issuer:Pubkey,
+    pub subject:Pubkey,
+    pub claims:u32,
+    pub expires_at:i64,
+    pub attestation_bump:u8,
+}
+
+impl Attestation{
+    pub const VERSION:u8 = 1;
+
+    //listings without required claims take any renter
+    pub fn require_claims(required_claims:u32,issuer:Option<&AttestationIssuer>,attestation:Option<&Attestation>,now:i64)->Result<()>{
+        if required_claims == 0 {
+            return Ok(());
+        }
+
+        let (Some(issuer),Some(attestation)) = (issuer,attestation) else {
+            return err!(ErrorCode::MissingAttestation);
+        };
+        require!(issuer.active && issuer.issuer == attestation.issuer,ErrorCode::InactiveIssuer);
+        require!(attestation.expires_at > now,ErrorCode::AttestationExpired);
+        require!(attestation.claims & required_claims == required_claims,ErrorCode::MissingClaims);
+
+        Ok(())
+    }
+}
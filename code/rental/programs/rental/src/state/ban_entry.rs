@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq,InitSpace)]
+pub enum BanReason{
+    LostDispute,
+    //kept the car past the grace period and the owner claimed the escrow
+    OverdueDefault,
+    Manual{code:u16},
+}
+
+//protocol wide ban of one wallet, the account existing is the ban
+#[account]
+#[derive(InitSpace)]
+pub struct BanEntry{
+    pub version:u8,
+    pub wallet:Pubkey,
+    pub reason:BanReason,
+    pub banned_at:i64,
+    pub ban_bump:u8,
+}
+
+impl BanEntry{
+    pub const VERSION:u8 = 1;
+
+    pub fn require_not_banned(ban_entry:&AccountInfo)->Result<()>{
+        require!(ban_entry.data_is_empty(),ErrorCode::RenterBanned);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::constants::BPS_DENOMINATOR;
+
+//settings that can only change through the propose -> apply timelock
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct ConfigParams{
+    pub arbitrator:Pubkey,
+    pub protocol_fee_bps:u16,
+    pub referral_fee_bps:u16,
+    pub timelock:i64,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct PendingConfig{
+    pub params:ConfigParams,
+    pub effective_at:i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config{
+    pub version:u8,
+    pub admin:Pubkey,
+    pub arbitrator:Pubkey,
+    pub protocol_fee_bps:u16,
+    pub referral_fee_bps:u16,
+    pub timelock:i64,
+    pub pending:Option<PendingConfig>,
+    pub config_bump:u8,
+}
+
+impl Config{
+    pub const VERSION:u8 = 1;
+
+    pub fn set_params(&mut self,params:ConfigParams){
+        self.arbitrator = params.arbitrator;
+        self.protocol_fee_bps = params.protocol_fee_bps;
+        self.referral_fee_bps = params.referral_fee_bps;
+        self.timelock = params.timelock;
+    }
+
+    pub fn protocol_fee(&self,amount:u64)->Option<u64>{
+        bps_share(amount,self.protocol_fee_bps)
+    }
+}
+
+pub fn bps_share(amount:u64,bps:u16)->Option<u64>{
+    let share = (amount as u128)
+        .checked_mul(bps as u128)?
+        .checked_div(BPS_DENOMINATOR as u128)?;
+    u64::try_from(share).ok()
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, instructions::ListingOptions, state::*};
+
+//interchangeable cars from one collection under shared pricing, units sit in ATAs owned by this account
+#[account]
+#[derive(InitSpace)]
+pub struct FleetPool{
+    pub version:u8,
+    pub owner:Pubkey,
+    pub collection_mint:Pubkey,
+    pub rent_fee:u64,
+    pub deposit_amount:u64,
+    pub referral_fee_bps:Option<u16>,
+    pub transfer_fee_payer:TransferFeePayer,
+    pub payment_kind:PaymentKind,
+    //the only mint units are paid in, none for lamport fleets
+    pub rent_fee_mint:Option<Pubkey>,
+    pub rented_units:u16,
+    #[max_len(MAX_FLEET_UNITS)]
+    pub available:Vec<Pubkey>,
+    pub required_claims:u32,
+    pub allowlist:Option<Pubkey>,
+    pub blocklist:Option<Pubkey>,
+    pub fleet_bump:u8,
+}
+
+impl FleetPool{
+    pub const VERSION:u8 = 1;
+
+    pub fn signer_seeds(&self)->Vec<Vec<u8>>{
+        vec![
+            b"fleet".to_vec(),
+            self.owner.to_bytes().to_vec(),
+            self.collection_mint.to_bytes().to_vec(),
+            vec![self.fleet_bump],
+        ]
+    }
+
+    //the shared pricing every unit is rented under
+    pub fn unit_options(&self)->ListingOptions{
+        ListingOptions{
+            referral_fee_bps:self.referral_fee_bps,
+            transfer_fee_payer:self.transfer_fee_payer,
+            payment_kind:self.payment_kind,
+            custody:CustodyMode::Vault,
+            denomination:PriceDenomination::Token,
+            accepted_payments:Vec::new(),
+            deposit_tiers:Vec::new(),
+            required_claims:self.required_claims,
+            allowlist:self.allowlist,
+            blocklist:self.blocklist,
+            payees:Vec::new(),
+        }
+    }
+}
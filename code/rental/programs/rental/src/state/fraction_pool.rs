@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TransferChecked};
+use crate::{constants::REWARD_PRECISION, errors::ErrorCode, utils::transfer_checked_with_hook};
+
+//share holders of a fractionalized listing, rent lands in the reward vault and is accounted lazily on the next stake, unstake or claim
+//rent is spread over every share but only staked positions can claim theirs, the part earned by unstaked shares stays in the vault until the car is redeemed
+#[account]
+#[derive(InitSpace)]
+pub struct FractionPool{
+    pub version:u8,
+    pub rental_state:Pubkey,
+    pub share_mint:Pubkey,
+    pub reward_mint:Pubkey,
+    pub total_shares:u64,
+    pub staked_shares:u64,
+    pub acc_reward_per_share:u128,
+    //reward vault balance already spread over the shares and not yet claimed
+    pub accounted_rewards:u64,
+    pub pool_bump:u8,
+}
+
+impl FractionPool{
+    pub const VERSION:u8 = 1;
+
+    pub fn signer_seeds(&self)->Vec<Vec<u8>>{
+        vec![
+            b"fraction".to_vec(),
+            self.rental_state.to_bytes().to_vec(),
+            vec![self.pool_bump],
+        ]
+    }
+
+    //spreads whatever arrived since the last sync over all shares, so a late staker never picks up rent from before they staked
+    pub fn sync(&mut self,reward_balance:u64)->Result<()>{
+        let arrived = reward_balance.checked_sub(self.accounted_rewards).ok_or(ErrorCode::ValueOverflow)?;
+        let per_share = (arrived as u128).checked_mul(REWARD_PRECISION).ok_or(ErrorCode::ValueOverflow)? / self.total_shares as u128;
+
+        self.acc_reward_per_share = self.acc_reward_per_share.checked_add(per_share).ok_or(ErrorCode::ValueOverflow)?;
+        self.accounted_rewards = reward_balance;
+
+        Ok(())
+    }
+
+    //syncs and books the position's pending rewards as paid, returns what to transfer
+    pub fn settle(&mut self,position:&mut SharePosition,reward_balance:u64)->Result<u64>{
+        self.sync(reward_balance)?;
+
+        let pending = position.accrued(self)?.checked_sub(position.reward_debt).ok_or(ErrorCode::ValueOverflow)?;
+        let pending = u64::try_from(pending).map_err(|_| ErrorCode::ValueOverflow)?;
+
+        self.accounted_rewards = self.accounted_rewards.checked_sub(pending).ok_or(ErrorCode::ValueOverflow)?;
+        position.reward_debt = position.accrued(self)?;
+
+        Ok(pending)
+    }
+
+    //pays out of the reward vault on the pool's signature, resolving any transfer hook from remaining accounts
+    pub fn pay_rewards<'info>(pool:&Account<'info,FractionPool>,token_program:AccountInfo<'info>,reward_vault:AccountInfo<'info>,reward_mint:&InterfaceAccount<'info,Mint>,to:AccountInfo<'info>,amount:u64,remaining_accounts:&[AccountInfo<'info>])->Result<()>{
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let seeds = pool.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        let cpi_accounts = TransferChecked{
+            from:reward_vault,
+            to,
+            mint:reward_mint.to_account_info(),
+            authority:pool.to_account_info(),
+        };
+
+        let signer_seed = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(token_program,cpi_accounts,signer_seed).with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_checked_with_hook(ctx,amount,reward_mint.decimals)
+    }
+}
+
+//one holder's staked shares, reward_debt is what their shares had already earned when last settled
+#[account]
+#[derive(InitSpace)]
+pub struct SharePosition{
+    pub version:u8,
+    pub pool:Pubkey,
+    pub holder:Pubkey,
+    pub shares:u64,
+    pub reward_debt:u128,
+    pub position_bump:u8,
+}
+
+impl SharePosition{
+    pub const VERSION:u8 = 1;
+
+    pub fn accrued(&self,pool:&FractionPool)->Result<u128>{
+        (self.shares as u128).checked_mul(pool.acc_reward_per_share).map(|accrued| accrued / REWARD_PRECISION).ok_or(ErrorCode::ValueOverflow.into())
+    }
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+//superseded account layouts, only read by the migrate instructions
+//every account type's VERSION 1 is its first deployed layout, so fields added before a type ships need no bump.
+//changing a deployed layout bumps its VERSION and adds the old layout here with a migrate arm that reads it
+
+//written before accounts carried a version
+#[derive(AnchorSerialize,AnchorDeserialize,InitSpace)]
+pub struct RentalStateV0 {
+    pub owner:Pubkey,
+    pub renter:Option<Pubkey>,
+    pub car_nft_mint:Pubkey,
+    pub rent_fee:u64,
+    pub rental_duration:Option<i64>,
+    pub rental_start_time:Option<i64>,
+    pub deposit_amount:u64,
+    pub rental_bump:u8,
+    pub listed:bool,
+    pub rented:bool,
+    pub status:StatusData,
+    pub dispute_caller:Option<Pubkey>,
+    pub test_num:Option<u64>
+}
+
+//v0 listings are vault custodied nfts priced in a single token, and an open rental took the listed amounts at face value
+impl From<RentalStateV0> for RentalState{
+    fn from(old:RentalStateV0)->Self{
+        let (escrowed_rent,escrowed_deposit) = if old.rented {
+            (old.rent_fee,old.deposit_amount)
+        } else {
+            (0,0)
+        };
+
+        RentalState{
+            version:RentalState::VERSION,
+            owner:old.owner,
+            renter:old.renter,
+            car_nft_mint:old.car_nft_mint,
+            rent_fee:old.rent_fee,
+            rental_duration:old.rental_duration,
+            rental_start_time:old.rental_start_time,
+            deposit_amount:old.deposit_amount,
+            rental_bump:old.rental_bump,
+            listed:old.listed,
+            rented:old.rented,
+            status:old.status,
+            dispute_caller:old.dispute_caller,
+            referral_fee_bps:None,
+            referrer:None,
+            transfer_fee_payer:TransferFeePayer::Owner,
+            escrowed_rent,
+            escrowed_deposit,
+            payment_kind:PaymentKind::Token,
+            sol_vault_bump:None,
+            custody:CustodyMode::Vault,
+            fleet:None,
+            denomination:PriceDenomination::Token,
+            rent_fee_mint:None,
+            payment_mint:None,
+            accepted_payments:Vec::new(),
+            deposit_tiers:Vec::new(),
+            required_claims:0,
+            allowlist:None,
+            blocklist:None,
+            payees:Vec::new(),
+            fraction_pool:None,
+            mileage:None,
+            start_odometer:None,
+        }
+    }
+}
@@ -1,2 +1,41 @@
 pub mod rental_state;
-pub use rental_state::*;
\ No newline at end of file
+pub use rental_state::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod multisig;
+pub use multisig::*;
+
+pub mod legacy;
+pub use legacy::*;
+
+pub mod referrer_stats;
+pub use referrer_stats::*;
+
+pub mod fleet_pool;
+pub use fleet_pool::*;
+
+pub mod price_feed;
+pub use price_feed::*;
+
+pub mod user_profile;
+pub use user_profile::*;
+
+pub mod rental_receipt;
+pub use rental_receipt::*;
+
+pub mod attestation;
+pub use attestation::*;
+
+pub mod renter_list;
+pub use renter_list::*;
+
+pub mod ban_entry;
+pub use ban_entry::*;
+
+pub mod operator_grant;
+pub use operator_grant::*;
+
+pub mod fraction_pool;
+pub use fraction_pool::*;
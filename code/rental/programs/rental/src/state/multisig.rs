@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use crate::{constants::MAX_MULTISIG_MEMBERS, errors::ErrorCode, state::{ConfigParams, PriceSource}};
+
+//the protocol authority: admin-gated instructions run only off an approved proposal
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig{
+    pub version:u8,
+    #[max_len(MAX_MULTISIG_MEMBERS)]
+    pub members:Vec<Pubkey>,
+    pub threshold:u8,
+    pub proposal_count:u64,
+    pub multisig_bump:u8,
+}
+
+impl Multisig{
+    pub const VERSION:u8 = 1;
+
+    pub fn validate_members(members:&[Pubkey],threshold:u8)->Result<()>{
+        require!(!members.is_empty() && members.len() <= MAX_MULTISIG_MEMBERS,ErrorCode::InvalidMembers);
+        for (i,member) in members.iter().enumerate(){
+            require!(!members[..i].contains(member),ErrorCode::InvalidMembers);
+        }
+        require!(threshold > 0 && threshold as usize <= members.len(),ErrorCode::InvalidThreshold);
+        Ok(())
+    }
+
+    pub fn is_member(&self,key:&Pubkey)->bool{
+        self.members.contains(key)
+    }
+}
+
+//borsh tags are the variant index, so new actions are only ever appended
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub enum ProposalAction{
+    ProposeConfig{params:ConfigParams},
+    CancelConfig,
+    WithdrawTreasury{mint:Pubkey,destination:Pubkey,amount:u64},
+    UpdateMultisig{
+        #[max_len(MAX_MULTISIG_MEMBERS)]
+        members:Vec<Pubkey>,
+        threshold:u8,
+    },
+    WithdrawTreasuryLamports{destination:Pubkey,amount:u64},
+    SetPriceFeed{mint:Pubkey,source:PriceSource,max_staleness:i64,max_confidence_bps:u16},
+    SetAttestationIssuer{issuer:Pubkey,active:bool},
+    BanWallet{wallet:Pubkey,code:u16},
+    UnbanWallet{wallet:Pubkey},
+    ApproveReferrer{referrer:Pubkey,approved:bool},
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal{
+    pub version:u8,
+    pub multisig:Pubkey,
+    pub proposer:Pubkey,
+    pub index:u64,
+    pub action:ProposalAction,
+    #[max_len(MAX_MULTISIG_MEMBERS)]
+    pub approvers:Vec<Pubkey>,
+    pub executed:bool,
+    pub proposal_bump:u8,
+}
+
+impl Proposal{
+    pub const VERSION:u8 = 1;
+
+    //approvals from keys that have since been removed from the signer set no longer count
+    pub fn approval_count(&self,multisig:&Multisig)->usize{
+        self.approvers.iter().filter(|approver| multisig.is_member(approver)).count()
+    }
+
+    pub fn execute(&mut self,multisig:&Multisig)->Result<()>{
+        require!(!self.executed,ErrorCode::ProposalAlreadyExecuted);
+        require!(self.approval_count(multisig) >= multisig.threshold as usize,ErrorCode::ThresholdNotMet);
+        self.executed = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+//an owner's delegation to an operator key, see the OPERATOR_* bits
+#[account]
+#[derive(InitSpace)]
+pub struct OperatorGrant{
+    pub version:u8,
+    pub owner:Pubkey,
+    pub operator:Pubkey,
+    pub permissions:u8,
+    pub grant_bump:u8,
+}
+
+impl OperatorGrant{
+    pub const VERSION:u8 = 1;
+
+    //the owner can always act, anyone else needs a grant carrying the permission
+    pub fn require_permission(owner:&Pubkey,authority:&Pubkey,grant:Option<&OperatorGrant>,permission:u8)->Result<()>{
+        if authority == owner {
+            return Ok(());
+        }
+
+        let Some(grant) = grant else {
+            return err!(ErrorCode::OperatorNotAuthorized);
+        };
+        require!(grant.owner == *owner && grant.operator == *authority,ErrorCode::OperatorNotAuthorized);
+        require!(grant.permissions & permission == permission,ErrorCode::OperatorNotAuthorized);
+
+        Ok(())
+    }
+}
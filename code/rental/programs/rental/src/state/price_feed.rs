@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+//where a payment mint's price comes from, mock feeds are pinned by address so tests can preload one
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub enum PriceSource{
+    Pyth{feed_id:[u8;32]},
+    Mock{account:Pubkey},
+}
+
+//per payment mint oracle settings, registered through the multisig
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed{
+    pub version:u8,
+    pub mint:Pubkey,
+    pub source:PriceSource,
+    pub max_staleness:i64,
+    pub max_confidence_bps:u16,
+    pub price_feed_bump:u8,
+}
+
+impl PriceFeed{
+    pub const VERSION:u8 = 1;
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+//running totals for a growth partner, amounts are in rent token base units
+#[account]
+#[derive(InitSpace)]
+pub struct ReferrerStats{
+    pub version:u8,
+    pub referrer:Pubkey,
+    pub rentals_referred:u64,
+    pub rentals_settled:u64,
+    pub total_earned:u64,
+    pub referrer_bump:u8,
+    //set by the multisig, renters can only name approved referrers
+    pub approved:bool,
+}
+
+impl ReferrerStats{
+    pub const VERSION:u8 = 1;
+
+    pub fn record_settled(&mut self,earned:u64)->Result<()>{
+        self.rentals_settled = self.rentals_settled.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        self.total_earned = self.total_earned.checked_add(earned).ok_or(ErrorCode::ValueOverflow)?;
+        Ok(())
+    }
+
+    //a disputed rental pays no referral fee but still closes out the referral it was rented under
+    pub fn record_exit(referrer:Option<Pubkey>,referrer_stats:Option<&mut ReferrerStats>)->Result<()>{
+        let Some(referrer) = referrer else {
+            return Ok(());
+        };
+
+        let Some(referrer_stats) = referrer_stats else {
+            return err!(ErrorCode::InvalidReferrer);
+        };
+        require_keys_eq!(referrer_stats.referrer,referrer,ErrorCode::InvalidReferrer);
+
+        referrer_stats.record_settled(0)
+    }
+}
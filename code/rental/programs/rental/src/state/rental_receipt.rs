@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct Rating{
+    pub score:u8,
+    pub review_hash:Option<[u8;32]>,
+}
+
+//left behind by a completed rental so its two parties can rate each other once, closed when both have
+#[account]
+#[derive(InitSpace)]
+pub struct RentalReceipt{
+    pub version:u8,
+    pub rental_state:Pubkey,
+    pub rental_start_time:i64,
+    pub owner:Pubkey,
+    pub renter:Pubkey,
+    pub car_nft_mint:Pubkey,
+    pub ended_at:i64,
+    //rating the owner gave the renter
+    pub owner_rating:Option<Rating>,
+    //rating the renter gave the owner
+    pub renter_rating:Option<Rating>,
+    pub receipt_bump:u8,
+}
+
+impl RentalReceipt{
+    pub const VERSION:u8 = 1;
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::{constants::*, instructions::ListingOptions, state::UserProfile};
 #[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq)]
 pub enum StatusData{
     Active,
@@ -12,11 +13,87 @@ impl anchor_lang::Space for StatusData{
     const INIT_SPACE:usize= 1;
 }
 
+//which side absorbs token-2022 transfer fees on the rent and deposit legs
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq)]
+pub enum TransferFeePayer{
+    Renter,
+    Owner
+}
+
+impl anchor_lang::Space for TransferFeePayer{
+    const INIT_SPACE:usize= 1;
+}
+
+//what rent and deposit are priced in, lamport listings escrow into a sol_vault PDA
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq)]
+pub enum PaymentKind{
+    Token,
+    Lamports
+}
+
+impl anchor_lang::Space for PaymentKind{
+    const INIT_SPACE:usize= 1;
+}
+
+//vault listings move the nft to the rental_state, frozen listings leave it with the owner under a rental_state freeze
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq)]
+pub enum CustodyMode{
+    Vault,
+    Frozen
+}
+
+impl anchor_lang::Space for CustodyMode{
+    const INIT_SPACE:usize= 1;
+}
+
+//token listings charge rent_fee and deposit_amount as is, reference listings price them in REFERENCE_DECIMALS units converted at rent time
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq)]
+pub enum PriceDenomination{
+    Token,
+    Reference
+}
+
+impl anchor_lang::Space for PriceDenomination{
+    const INIT_SPACE:usize= 1;
+}
+
+//an alternative token the owner accepts, with its own rent and deposit in that token's base units
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct AcceptedPayment{
+    pub mint:Pubkey,
+    pub rent_fee:u64,
+    pub deposit_amount:u64,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct Payee{
+    pub wallet:Pubkey,
+    pub share_bps:u16,
+}
+
+//renters whose profile clears both bars get `discount_bps` off the deposit, the best matching tier wins
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct DepositTier{
+    pub min_completed_rentals:u64,
+    pub max_disputes_lost:u64,
+    pub discount_bps:u16,
+}
+
+//a car's telematics device signs odometer readings, km past included_km are charged from the deposit
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,PartialEq,InitSpace)]
+pub struct MileagePricing{
+    pub device:Pubkey,
+    pub included_km:u64,
+    pub fee_per_km:u64,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct RentalState {
+    pub version:u8,
     pub owner:Pubkey,
     pub renter:Option<Pubkey>,
+    //mint of the car nft, or the asset address for mpl-core cars
     pub car_nft_mint:Pubkey,
     pub rent_fee:u64,
     pub rental_duration:Option<i64>,
@@ -27,5 +104,125 @@ pub struct RentalState {
     pub rented:bool,
     pub status:StatusData,
     pub dispute_caller:Option<Pubkey>,
-    pub test_num:Option<u64>
+    pub referral_fee_bps:Option<u16>,
+    pub referrer:Option<Pubkey>,
+    pub transfer_fee_payer:TransferFeePayer,
+    pub escrowed_rent:u64,
+    pub escrowed_deposit:u64,
+    pub payment_kind:PaymentKind,
+    pub sol_vault_bump:Option<u8>,
+    pub custody:CustodyMode,
+    //set on rentals of a fleet unit, which settle through end_fleet_rental
+    pub fleet:Option<Pubkey>,
+    pub denomination:PriceDenomination,
+    //mint rent_fee and deposit_amount are priced in, unset for lamport and pre-upgrade listings
+    pub rent_fee_mint:Option<Pubkey>,
+    //mint the renter chose at rent_car, settlement only accepts this one
+    pub payment_mint:Option<Pubkey>,
+    #[max_len(MAX_ACCEPTED_PAYMENTS)]
+    pub accepted_payments:Vec<AcceptedPayment>,
+    #[max_len(MAX_DEPOSIT_TIERS)]
+    pub deposit_tiers:Vec<DepositTier>,
+    //claims bitmap the renter's attestation must carry, zero for listings open to anyone
+    pub required_claims:u32,
+    //owner renter lists this listing checks, see RenterList
+    pub allowlist:Option<Pubkey>,
+    pub blocklist:Option<Pubkey>,
+    //co-owners and managers splitting the owner's side, empty pays the owner alone
+    #[max_len(MAX_PAYEES)]
+    pub payees:Vec<Payee>,
+    //fractionalized listings keep the car locked between rentals and pay the owner side into this pool
+    pub fraction_pool:Option<Pubkey>,
+    //per km billing against device signed odometer readings, start_odometer is the reading taken at rent_car
+    pub mileage:Option<MileagePricing>,
+    pub start_odometer:Option<u64>,
+}
+
+impl RentalState{
+    pub const VERSION:u8 = 1;
+
+    //an unrented listing of `car_nft_mint`, every listing and fleet instruction starts from this
+    pub fn new_listing(owner:Pubkey,car_nft_mint:Pubkey,rent_fee_mint:Option<Pubkey>,rent_fee:u64,deposit_amount:u64,options:ListingOptions,(rental_bump,sol_vault_bump):(u8,Option<u8>))->Self{
+        RentalState{
+            version:RentalState::VERSION,
+            owner,
+            renter:None,
+            rental_duration:None,
+            car_nft_mint,
+            rent_fee,
+            rental_start_time:None,
+            deposit_amount,
+            rental_bump,
+            listed:true,
+            rented:false,
+            status:StatusData::Active,
+            dispute_caller:None,
+            referral_fee_bps:options.referral_fee_bps,
+            referrer:None,
+            transfer_fee_payer:options.transfer_fee_payer,
+            escrowed_rent:0,
+            escrowed_deposit:0,
+            payment_kind:options.payment_kind,
+            sol_vault_bump,
+            custody:options.custody,
+            fleet:None,
+            denomination:options.denomination,
+            rent_fee_mint,
+            payment_mint:None,
+            accepted_payments:options.accepted_payments,
+            deposit_tiers:options.deposit_tiers,
+            required_claims:options.required_claims,
+            allowlist:options.allowlist,
+            blocklist:options.blocklist,
+            payees:options.payees,
+            fraction_pool:None,
+            mileage:None,
+            start_odometer:None,
+        }
+    }
+
+    pub fn signer_seeds(&self)->Vec<Vec<u8>>{
+        vec![
+            b"rental".to_vec(),
+            self.car_nft_mint.to_bytes().to_vec(),
+            self.owner.to_bytes().to_vec(),
+            vec![self.rental_bump],
+        ]
+    }
+
+    //returned after the rental window closed, rentals without a window are never late
+    pub fn is_late(&self,now:i64)->bool{
+        match (self.rental_start_time,self.rental_duration) {
+            (Some(start),Some(duration)) => now > start.saturating_add(duration),
+            _ => false,
+        }
+    }
+
+    //rent and deposit owed when paying in `mint`. listings from before the upgrade have no mint on record
+    //and can't be rented until the owner pins one with update_listing_price
+    pub fn price_in(&self,mint:&Pubkey)->Option<(u64,u64)>{
+        match self.rent_fee_mint {
+            Some(rent_fee_mint) if rent_fee_mint == *mint => Some((self.rent_fee,self.deposit_amount)),
+            Some(_) => self.accepted_payments.iter()
+                .find(|payment| payment.mint == *mint)
+                .map(|payment| (payment.rent_fee,payment.deposit_amount)),
+            None => None,
+        }
+    }
+
+    //deposit after the renter's tier discount, rounded in the owner's favour, renters without a profile pay in full
+    pub fn tiered_deposit(&self,deposit:u64,profile:Option<&UserProfile>)->Option<u64>{
+        let Some(profile) = profile else {
+            return Some(deposit);
+        };
+
+        let discount_bps = self.deposit_tiers.iter()
+            .filter(|tier| profile.completed_rentals >= tier.min_completed_rentals && profile.disputes_lost <= tier.max_disputes_lost)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0);
+
+        let discount = (deposit as u128).checked_mul(discount_bps as u128)?.checked_div(BPS_DENOMINATOR as u128)?;
+        deposit.checked_sub(u64::try_from(discount).ok()?)
+    }
 }
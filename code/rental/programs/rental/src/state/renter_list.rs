@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::{constants::MAX_LIST_RENTERS, errors::ErrorCode};
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone,Copy,PartialEq)]
+pub enum RenterListKind{
+    Allow,
+    Block
+}
+
+impl anchor_lang::Space for RenterListKind{
+    const INIT_SPACE:usize= 1;
+}
+
+//wallets an owner lets in or keeps out, shared by every listing that references it
+#[account]
+#[derive(InitSpace)]
+pub struct RenterList{
+    pub version:u8,
+    pub owner:Pubkey,
+    pub list_id:u16,
+    pub kind:RenterListKind,
+    #[max_len(MAX_LIST_RENTERS)]
+    pub renters:Vec<Pubkey>,
+    pub list_bump:u8,
+}
+
+impl RenterList{
+    pub const VERSION:u8 = 1;
+
+    //listings without a reference let everyone through, otherwise that exact list has to be passed
+    pub fn require_renter(reference:Option<Pubkey>,list:Option<(Pubkey,&RenterList)>,kind:RenterListKind,owner:&Pubkey,renter:&Pubkey)->Result<()>{
+        let Some(reference) = reference else {
+            return Ok(());
+        };
+
+        let Some((key,list)) = list else {
+            return err!(ErrorCode::MissingRenterList);
+        };
+        require!(key == reference && list.kind == kind && list.owner == *owner,ErrorCode::InvalidRenterList);
+
+        match kind {
+            RenterListKind::Allow => require!(list.renters.contains(renter),ErrorCode::RenterNotAllowed),
+            RenterListKind::Block => require!(!list.renters.contains(renter),ErrorCode::RenterBlocked),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+//track record of a wallet on either side of a rental, volume sums raw base units of whichever mint was paid
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile{
+    pub version:u8,
+    pub user:Pubkey,
+    pub completed_rentals:u64,
+    pub total_volume:u64,
+    pub disputes_opened:u64,
+    pub disputes_lost:u64,
+    pub late_returns:u64,
+    //rentals the owner claimed after the renter kept the car past the grace period
+    pub overdue_defaults:u64,
+    //only counterparties of a completed rental can add to these, through its receipt
+    pub rating_count:u64,
+    pub rating_total:u64,
+    pub profile_bump:u8,
+}
+
+impl UserProfile{
+    pub const VERSION:u8 = 1;
+
+    //profiles are created on first use by whichever instruction touches them
+    pub fn init_if_new(&mut self,user:Pubkey,bump:u8){
+        if self.version == 0 {
+            self.version = UserProfile::VERSION;
+            self.user = user;
+            self.profile_bump = bump;
+        }
+    }
+
+    pub fn record_rental(&mut self,volume:u64,late:bool)->Result<()>{
+        self.completed_rentals = self.completed_rentals.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        self.total_volume = self.total_volume.saturating_add(volume);
+        if late {
+            self.late_returns = self.late_returns.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn record_dispute_opened(&mut self)->Result<()>{
+        self.disputes_opened = self.disputes_opened.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_dispute_lost(&mut self)->Result<()>{
+        self.disputes_lost = self.disputes_lost.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_overdue_default(&mut self)->Result<()>{
+        self.overdue_defaults = self.overdue_defaults.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_rating(&mut self,score:u8)->Result<()>{
+        self.rating_count = self.rating_count.checked_add(1).ok_or(ErrorCode::ValueOverflow)?;
+        self.rating_total = self.rating_total.checked_add(score as u64).ok_or(ErrorCode::ValueOverflow)?;
+        Ok(())
+    }
+}
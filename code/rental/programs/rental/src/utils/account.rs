@@ -0,0 +1,27 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer},
+};
+
+//create_account refuses an address that already holds lamports, and anyone can send lamports to a PDA before it exists,
+//so a pre-funded address is topped up to `lamports`, then allocated and assigned under its seeds instead
+pub fn create_pda_account<'info>(payer:AccountInfo<'info>,account:AccountInfo<'info>,system_program:AccountInfo<'info>,lamports:u64,space:u64,owner:&Pubkey,seeds:&[&[u8]])->Result<()>{
+
+    let current = account.lamports();
+
+    if current == 0 {
+        return create_account(
+            CpiContext::new_with_signer(system_program,CreateAccount{from:payer,to:account},&[seeds]),
+            lamports,
+            space,
+            owner,
+        );
+    }
+
+    if lamports > current {
+        transfer(CpiContext::new(system_program.clone(),Transfer{from:payer,to:account.clone()}),lamports - current)?;
+    }
+
+    allocate(CpiContext::new_with_signer(system_program.clone(),Allocate{account_to_allocate:account.clone()},&[seeds]),space)?;
+    assign(CpiContext::new_with_signer(system_program,Assign{account_to_assign:account},&[seeds]),owner)
+}
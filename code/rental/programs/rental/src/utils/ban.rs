@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, state::{BanEntry, BanReason}, utils::create_pda_account};
+
+//creates a wallet's ban entry from inside a settlement, where it can't be an init account
+pub struct Ban<'info>{
+    pub ban_entry:AccountInfo<'info>,
+    pub payer:AccountInfo<'info>,
+    pub system_program:AccountInfo<'info>,
+}
+
+impl<'info> Ban<'info>{
+
+    //a wallet that is already banned keeps its original entry
+    pub fn ban(&self,wallet:Pubkey,reason:BanReason,bump:u8)->Result<()>{
+
+        if !self.ban_entry.data_is_empty() {
+            return Ok(());
+        }
+
+        let space = DISCRIMINATOR + BanEntry::INIT_SPACE;
+        let seeds:&[&[u8]] = &[b"ban", wallet.as_ref(), &[bump]];
+
+        //the renter can fund their own ban address ahead of time, which must not block the ruling
+        create_pda_account(
+            self.payer.clone(),
+            self.ban_entry.clone(),
+            self.system_program.clone(),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+            seeds,
+        )?;
+
+        let entry = BanEntry{
+            version:BanEntry::VERSION,
+            wallet,
+            reason,
+            banned_at:Clock::get()?.unix_timestamp,
+            ban_bump:bump,
+        };
+
+        let mut data = self.ban_entry.try_borrow_mut_data()?;
+        entry.try_serialize(&mut &mut data[..])
+    }
+}
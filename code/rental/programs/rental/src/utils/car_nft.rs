@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{
+        freeze_delegated_account, thaw_delegated_account,
+        mpl_token_metadata::{
+            instructions::{
+                DelegateUtilityV1Cpi, DelegateUtilityV1CpiAccounts, DelegateUtilityV1InstructionArgs,
+                LockV1Cpi, LockV1CpiAccounts, LockV1InstructionArgs,
+                RevokeUtilityV1Cpi, RevokeUtilityV1CpiAccounts,
+                TransferV1Cpi, TransferV1CpiAccounts, TransferV1InstructionArgs,
+                UnlockV1Cpi, UnlockV1CpiAccounts, UnlockV1InstructionArgs,
+            },
+            types::TokenStandard,
+        },
+        FreezeDelegatedAccount, MetadataAccount, ThawDelegatedAccount,
+    },
+    token_interface::{approve, revoke, Approve, Mint, Revoke, TransferChecked},
+};
+
+use crate::{errors::ErrorCode, utils::transfer_checked_with_hook};
+
+//one side of a car nft transfer, the token record is only read for programmable nfts
+pub struct NftHolder<'info>{
+    pub token:AccountInfo<'info>,
+    pub owner:AccountInfo<'info>,
+    pub token_record:Option<AccountInfo<'info>>,
+}
+
+//extra accounts token-metadata needs to move a programmable nft
+pub struct PnftAccounts<'info>{
+    pub metadata_program:AccountInfo<'info>,
+    pub system_program:AccountInfo<'info>,
+    pub associated_token_program:AccountInfo<'info>,
+    pub sysvar_instructions:Option<AccountInfo<'info>>,
+    pub authorization_rules_program:Option<AccountInfo<'info>>,
+    pub authorization_rules:Option<AccountInfo<'info>>,
+}
+
+//pnft token accounts stay frozen, so they move through token-metadata instead of a plain transfer_checked
+pub struct CarNft<'a,'info>{
+    pub token_program:AccountInfo<'info>,
+    pub mint:AccountInfo<'info>,
+    pub decimals:u8,
+    pub metadata:AccountInfo<'info>,
+    pub edition:AccountInfo<'info>,
+    pub payer:AccountInfo<'info>,
+    pub programmable:bool,
+    pub pnft:PnftAccounts<'info>,
+    pub remaining_accounts:&'a [AccountInfo<'info>],
+}
+
+pub fn is_programmable(metadata:&MetadataAccount)->bool{
+    matches!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition)
+    )
+}
+
+impl<'a,'info> CarNft<'a,'info>{
+
+    pub fn new(token_program:AccountInfo<'info>,mint:&InterfaceAccount<'info,Mint>,metadata:&Account<'info,MetadataAccount>,edition:AccountInfo<'info>,payer:AccountInfo<'info>,pnft:PnftAccounts<'info>,remaining_accounts:&'a [AccountInfo<'info>])->Self{
+        CarNft{
+            token_program,
+            mint:mint.to_account_info(),
+            decimals:mint.decimals,
+            programmable:is_programmable(metadata),
+            metadata:metadata.to_account_info(),
+            edition,
+            payer,
+            pnft,
+            remaining_accounts,
+        }
+    }
+
+    //`from.owner` is the transfer authority, pass the rental_state seeds when it is the vault
+    pub fn transfer(&self,from:NftHolder<'info>,to:NftHolder<'info>,signer_seeds:&[&[&[u8]]])->Result<()>{
+
+        if !self.programmable {
+            let cpi_accounts = TransferChecked{
+                from:from.token,
+                to:to.token,
+                mint:self.mint.clone(),
+                authority:from.owner
+            };
+
+            let ctx = CpiContext::new_with_signer(self.token_program.clone(),cpi_accounts,signer_seeds).with_remaining_accounts(self.remaining_accounts.to_vec());
+
+            return transfer_checked_with_hook(ctx, 1,self.decimals);
+        }
+
+        let (Some(token_record),Some(destination_token_record),Some(sysvar_instructions)) = (&from.token_record,&to.token_record,&self.pnft.sysvar_instructions) else {
+            return err!(ErrorCode::MissingPnftAccounts);
+        };
+
+        TransferV1Cpi::new(
+            &self.pnft.metadata_program,
+            TransferV1CpiAccounts{
+                token:&from.token,
+                token_owner:&from.owner,
+                destination_token:&to.token,
+                destination_owner:&to.owner,
+                mint:&self.mint,
+                metadata:&self.metadata,
+                edition:Some(&self.edition),
+                token_record:Some(token_record),
+                destination_token_record:Some(destination_token_record),
+                authority:&from.owner,
+                payer:&self.payer,
+                system_program:&self.pnft.system_program,
+                sysvar_instructions,
+                spl_token_program:&self.token_program,
+                spl_ata_program:&self.pnft.associated_token_program,
+                authorization_rules_program:self.pnft.authorization_rules_program.as_ref(),
+                authorization_rules:self.pnft.authorization_rules.as_ref(),
+            },
+            TransferV1InstructionArgs{
+                amount:1,
+                authorization_data:None,
+            },
+        ).invoke_signed(signer_seeds)?;
+
+        Ok(())
+    }
+
+    //leaves the nft with its holder and has `delegate` freeze it in place, pnfts use a utility delegate and lock
+    pub fn lock(&self,holder:NftHolder<'info>,delegate:AccountInfo<'info>,delegate_seeds:&[&[&[u8]]])->Result<()>{
+
+        if !self.programmable {
+            let cpi_accounts = Approve{
+                to:holder.token.clone(),
+                delegate:delegate.clone(),
+                authority:holder.owner,
+            };
+
+            approve(CpiContext::new(self.token_program.clone(),cpi_accounts),1)?;
+
+            let cpi_accounts = FreezeDelegatedAccount{
+                metadata:self.metadata.clone(),
+                delegate,
+                token_account:holder.token,
+                edition:self.edition.clone(),
+                mint:self.mint.clone(),
+                token_program:self.token_program.clone(),
+            };
+
+            return freeze_delegated_account(CpiContext::new_with_signer(self.pnft.metadata_program.clone(),cpi_accounts,delegate_seeds));
+        }
+
+        let (Some(token_record),Some(sysvar_instructions)) = (&holder.token_record,&self.pnft.sysvar_instructions) else {
+            return err!(ErrorCode::MissingPnftAccounts);
+        };
+
+        DelegateUtilityV1Cpi::new(
+            &self.pnft.metadata_program,
+            DelegateUtilityV1CpiAccounts{
+                delegate_record:None,
+                delegate:&delegate,
+                metadata:&self.metadata,
+                master_edition:Some(&self.edition),
+                token_record:Some(token_record),
+                mint:&self.mint,
+                token:&holder.token,
+                authority:&holder.owner,
+                payer:&self.payer,
+                system_program:&self.pnft.system_program,
+                sysvar_instructions,
+                spl_token_program:Some(&self.token_program),
+                authorization_rules_program:self.pnft.authorization_rules_program.as_ref(),
+                authorization_rules:self.pnft.authorization_rules.as_ref(),
+            },
+            DelegateUtilityV1InstructionArgs{
+                amount:1,
+                authorization_data:None,
+            },
+        ).invoke()?;
+
+        LockV1Cpi::new(
+            &self.pnft.metadata_program,
+            LockV1CpiAccounts{
+                authority:&delegate,
+                token_owner:Some(&holder.owner),
+                token:&holder.token,
+                mint:&self.mint,
+                metadata:&self.metadata,
+                edition:Some(&self.edition),
+                token_record:Some(token_record),
+                payer:&self.payer,
+                system_program:&self.pnft.system_program,
+                sysvar_instructions,
+                spl_token_program:Some(&self.token_program),
+                authorization_rules_program:self.pnft.authorization_rules_program.as_ref(),
+                authorization_rules:self.pnft.authorization_rules.as_ref(),
+            },
+            LockV1InstructionArgs{
+                authorization_data:None,
+            },
+        ).invoke_signed(delegate_seeds)?;
+
+        Ok(())
+    }
+
+    //reverses `lock`, the holder signs to revoke the delegate once the token is thawed
+    pub fn unlock(&self,holder:NftHolder<'info>,delegate:AccountInfo<'info>,delegate_seeds:&[&[&[u8]]])->Result<()>{
+
+        if !self.programmable {
+            let cpi_accounts = ThawDelegatedAccount{
+                metadata:self.metadata.clone(),
+                delegate,
+                token_account:holder.token.clone(),
+                edition:self.edition.clone(),
+                mint:self.mint.clone(),
+                token_program:self.token_program.clone(),
+            };
+
+            thaw_delegated_account(CpiContext::new_with_signer(self.pnft.metadata_program.clone(),cpi_accounts,delegate_seeds))?;
+
+            let cpi_accounts = Revoke{
+                source:holder.token,
+                authority:holder.owner,
+            };
+
+            return revoke(CpiContext::new(self.token_program.clone(),cpi_accounts));
+        }
+
+        let (Some(token_record),Some(sysvar_instructions)) = (&holder.token_record,&self.pnft.sysvar_instructions) else {
+            return err!(ErrorCode::MissingPnftAccounts);
+        };
+
+        UnlockV1Cpi::new(
+            &self.pnft.metadata_program,
+            UnlockV1CpiAccounts{
+                authority:&delegate,
+                token_owner:Some(&holder.owner),
+                token:&holder.token,
+                mint:&self.mint,
+                metadata:&self.metadata,
+                edition:Some(&self.edition),
+                token_record:Some(token_record),
+                payer:&self.payer,
+                system_program:&self.pnft.system_program,
+                sysvar_instructions,
+                spl_token_program:Some(&self.token_program),
+                authorization_rules_program:self.pnft.authorization_rules_program.as_ref(),
+                authorization_rules:self.pnft.authorization_rules.as_ref(),
+            },
+            UnlockV1InstructionArgs{
+                authorization_data:None,
+            },
+        ).invoke_signed(delegate_seeds)?;
+
+        RevokeUtilityV1Cpi::new(
+            &self.pnft.metadata_program,
+            RevokeUtilityV1CpiAccounts{
+                delegate_record:None,
+                delegate:&delegate,
+                metadata:&self.metadata,
+                master_edition:Some(&self.edition),
+                token_record:Some(token_record),
+                mint:&self.mint,
+                token:&holder.token,
+                authority:&holder.owner,
+                payer:&self.payer,
+                system_program:&self.pnft.system_program,
+                sysvar_instructions,
+                spl_token_program:Some(&self.token_program),
+                authorization_rules_program:self.pnft.authorization_rules_program.as_ref(),
+                authorization_rules:self.pnft.authorization_rules.as_ref(),
+            },
+        ).invoke()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::{AccountMeta, Instruction}, keccak, program::invoke_signed},
+};
+
+use crate::errors::ErrorCode;
+
+pub const BUBBLEGUM_PROGRAM_ID:Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const SPL_ACCOUNT_COMPRESSION_ID:Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_ID:Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+const TRANSFER_DISCRIMINATOR:[u8;8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+//bubblegum's MetadataArgs, field order and layout must match for the leaf hash
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct CompressedMetadata{
+    pub name:String,
+    pub symbol:String,
+    pub uri:String,
+    pub seller_fee_basis_points:u16,
+    pub primary_sale_happened:bool,
+    pub is_mutable:bool,
+    pub edition_nonce:Option<u8>,
+    pub token_standard:Option<CompressedTokenStandard>,
+    pub collection:Option<CompressedCollection>,
+    pub uses:Option<CompressedUses>,
+    pub token_program_version:CompressedTokenProgramVersion,
+    pub creators:Vec<CompressedCreator>,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub enum CompressedTokenStandard{
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct CompressedCollection{
+    pub verified:bool,
+    pub key:Pubkey,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub enum CompressedUseMethod{
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct CompressedUses{
+    pub use_method:CompressedUseMethod,
+    pub remaining:u64,
+    pub total:u64,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub enum CompressedTokenProgramVersion{
+    Original,
+    Token2022,
+}
+
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct CompressedCreator{
+    pub address:Pubkey,
+    pub verified:bool,
+    pub share:u8,
+}
+
+//everything needed to rebuild the car's leaf, the first `proof_len` remaining accounts are its merkle proof
+#[derive(AnchorSerialize,AnchorDeserialize,Clone)]
+pub struct CompressedLeaf{
+    pub asset_id:Pubkey,
+    pub root:[u8;32],
+    pub nonce:u64,
+    pub index:u32,
+    pub proof_len:u8,
+    pub metadata:CompressedMetadata,
+}
+
+impl CompressedLeaf{
+
+    pub fn verify_asset_id(&self,merkle_tree:&Pubkey)->Result<()>{
+        let (asset_id,_) = Pubkey::find_program_address(&[b"asset", merkle_tree.as_ref(), &self.nonce.to_le_bytes()],&BUBBLEGUM_PROGRAM_ID);
+        require_keys_eq!(asset_id,self.asset_id,ErrorCode::InvalidCompressedLeaf);
+        Ok(())
+    }
+
+    //a forged collection changes the data hash, so bubblegum rejects the leaf
+    pub fn verify_collection(&self,collection_mint:&Pubkey)->Result<()>{
+        match &self.metadata.collection {
+            Some(collection) if collection.key == *collection_mint => {
+                require!(collection.verified,ErrorCode::UnverifiedCollection);
+                Ok(())
+            },
+            Some(_) => err!(ErrorCode::InvalidCollection),
+            None => err!(ErrorCode::MissingCollection),
+        }
+    }
+
+    pub fn data_hash(&self)->Result<[u8;32]>{
+        let metadata_hash = keccak::hashv(&[&self.metadata.try_to_vec()?]);
+        Ok(keccak::hashv(&[&metadata_hash.to_bytes(),&self.metadata.seller_fee_basis_points.to_le_bytes()]).to_bytes())
+    }
+
+    pub fn creator_hash(&self)->[u8;32]{
+        let creators:Vec<Vec<u8>> = self.metadata.creators.iter()
+            .map(|creator| [creator.address.as_ref(),&[creator.verified as u8],&[creator.share]].concat())
+            .collect();
+        let creators:Vec<&[u8]> = creators.iter().map(|creator| creator.as_slice()).collect();
+        keccak::hashv(&creators).to_bytes()
+    }
+
+    //splits the proof off the front of remaining accounts, what is left goes to transfer hooks
+    pub fn split_proof<'a,'info>(&self,remaining_accounts:&'a [AccountInfo<'info>])->Result<(&'a [AccountInfo<'info>],&'a [AccountInfo<'info>])>{
+        require!(remaining_accounts.len() >= self.proof_len as usize,ErrorCode::InvalidCompressedLeaf);
+        Ok(remaining_accounts.split_at(self.proof_len as usize))
+    }
+}
+
+//moves a compressed car through bubblegum, the leaf owner doubles as its delegate
+pub struct CompressedCar<'info>{
+    pub bubblegum_program:AccountInfo<'info>,
+    pub tree_config:AccountInfo<'info>,
+    pub merkle_tree:AccountInfo<'info>,
+    pub log_wrapper:AccountInfo<'info>,
+    pub compression_program:AccountInfo<'info>,
+    pub system_program:AccountInfo<'info>,
+}
+
+impl<'info> CompressedCar<'info>{
+
+    //`leaf_owner` signs, pass the rental_state seeds when it is the rental_state
+    pub fn transfer(&self,leaf:&CompressedLeaf,proof:&[AccountInfo<'info>],leaf_owner:AccountInfo<'info>,new_leaf_owner:AccountInfo<'info>,signer_seeds:&[&[&[u8]]])->Result<()>{
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.tree_config.key(),false),
+            AccountMeta::new_readonly(leaf_owner.key(),true),
+            AccountMeta::new_readonly(leaf_owner.key(),false),
+            AccountMeta::new_readonly(new_leaf_owner.key(),false),
+            AccountMeta::new(self.merkle_tree.key(),false),
+            AccountMeta::new_readonly(self.log_wrapper.key(),false),
+            AccountMeta::new_readonly(self.compression_program.key(),false),
+            AccountMeta::new_readonly(self.system_program.key(),false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(),false)));
+
+        let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&leaf.root);
+        data.extend_from_slice(&leaf.data_hash()?);
+        data.extend_from_slice(&leaf.creator_hash());
+        data.extend_from_slice(&leaf.nonce.to_le_bytes());
+        data.extend_from_slice(&leaf.index.to_le_bytes());
+
+        let mut account_infos = vec![
+            self.tree_config.clone(),
+            leaf_owner,
+            new_leaf_owner,
+            self.merkle_tree.clone(),
+            self.log_wrapper.clone(),
+            self.compression_program.clone(),
+            self.system_program.clone(),
+            self.bubblegum_program.clone(),
+        ];
+        account_infos.extend_from_slice(proof);
+
+        invoke_signed(
+            &Instruction{
+                program_id:BUBBLEGUM_PROGRAM_ID,
+                accounts,
+                data,
+            },
+            &account_infos,
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+}
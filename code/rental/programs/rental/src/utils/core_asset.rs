@@ -0,0 +1,99 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::{AccountMeta, Instruction}, program::invoke_signed},
+};
+
+use crate::errors::ErrorCode;
+
+pub const MPL_CORE_PROGRAM_ID:Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+const ASSET_V1_KEY:u8 = 1;
+const COLLECTION_V1_KEY:u8 = 5;
+const TRANSFER_V1_DISCRIMINATOR:u8 = 14;
+
+#[derive(AnchorDeserialize)]
+pub enum CoreUpdateAuthority{
+    None,
+    Address(Pubkey),
+    Collection(Pubkey),
+}
+
+//leading fields of an mpl-core asset, name, uri and plugins follow and are not read
+#[derive(AnchorDeserialize)]
+pub struct CoreAsset{
+    pub key:u8,
+    pub owner:Pubkey,
+    pub update_authority:CoreUpdateAuthority,
+}
+
+impl CoreAsset{
+
+    pub fn load(asset:&AccountInfo)->Result<Self>{
+        require_keys_eq!(*asset.owner,MPL_CORE_PROGRAM_ID,ErrorCode::InvalidCoreAsset);
+
+        let data = asset.try_borrow_data()?;
+        let core_asset = CoreAsset::deserialize(&mut &data[..]).map_err(|_| ErrorCode::InvalidCoreAsset)?;
+        require!(core_asset.key == ASSET_V1_KEY,ErrorCode::InvalidCoreAsset);
+
+        Ok(core_asset)
+    }
+
+    //core assets belong to a collection through their update authority
+    pub fn verify_collection(&self,collection:&AccountInfo)->Result<()>{
+        require_keys_eq!(*collection.owner,MPL_CORE_PROGRAM_ID,ErrorCode::InvalidCollection);
+        require!(collection.try_borrow_data()?.first() == Some(&COLLECTION_V1_KEY),ErrorCode::InvalidCollection);
+
+        match self.update_authority {
+            CoreUpdateAuthority::Collection(key) if key == collection.key() => Ok(()),
+            _ => err!(ErrorCode::InvalidCollection),
+        }
+    }
+}
+
+//moves a core asset through mpl-core's transfer instruction, custody is the asset's owner field
+pub struct CoreCar<'info>{
+    pub core_program:AccountInfo<'info>,
+    pub asset:AccountInfo<'info>,
+    pub collection:AccountInfo<'info>,
+    pub payer:AccountInfo<'info>,
+    pub system_program:AccountInfo<'info>,
+}
+
+impl<'info> CoreCar<'info>{
+
+    //`authority` is the current owner, pass the rental_state seeds when it is the rental_state
+    pub fn transfer(&self,authority:AccountInfo<'info>,new_owner:AccountInfo<'info>,signer_seeds:&[&[&[u8]]])->Result<()>{
+
+        let ix = Instruction{
+            program_id:MPL_CORE_PROGRAM_ID,
+            accounts:vec![
+                AccountMeta::new(self.asset.key(),false),
+                AccountMeta::new_readonly(self.collection.key(),false),
+                AccountMeta::new(self.payer.key(),true),
+                AccountMeta::new_readonly(authority.key(),true),
+                AccountMeta::new_readonly(new_owner.key(),false),
+                AccountMeta::new_readonly(self.system_program.key(),false),
+                //no log wrapper
+                AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID,false),
+            ],
+            //TransferV1 with no compression proof
+            data:vec![TRANSFER_V1_DISCRIMINATOR,0],
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                self.asset.clone(),
+                self.collection.clone(),
+                self.payer.clone(),
+                authority,
+                new_owner,
+                self.system_program.clone(),
+                self.core_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+}
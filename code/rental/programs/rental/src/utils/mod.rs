@@ -0,0 +1,35 @@
+pub mod transfer_fee;
+pub use transfer_fee::*;
+
+pub mod transfer_hook;
+pub use transfer_hook::*;
+
+pub mod payment;
+pub use payment::*;
+
+pub mod car_nft;
+pub use car_nft::*;
+
+pub mod core_asset;
+pub use core_asset::*;
+
+pub mod compressed_nft;
+pub use compressed_nft::*;
+
+pub mod rental_pass;
+pub use rental_pass::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod ban;
+pub use ban::*;
+
+pub mod odometer;
+pub use odometer::*;
+
+pub mod settlement;
+pub use settlement::*;
+
+pub mod account;
+pub use account::*;
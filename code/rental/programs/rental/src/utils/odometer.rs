@@ -0,0 +1,73 @@
+use anchor_lang::{prelude::*, solana_program::{ed25519_program, sysvar::instructions::{load_current_index_checked, load_instruction_at_checked}}};
+
+use crate::{constants::*, errors::ErrorCode};
+
+//ed25519 program instruction data, a count and padding byte then one offsets entry per signature
+const ED25519_HEADER:usize = 2;
+const ED25519_OFFSETS:usize = 14;
+
+pub struct OdometerReading{
+    pub odometer_km:u64,
+    pub read_at:i64,
+}
+
+impl OdometerReading{
+
+    //reads the odometer from the ed25519 instruction right before the current one, signed by the car's device for this listing
+    pub fn load(sysvar_instructions:&AccountInfo,device:&Pubkey,rental_state:&Pubkey,now:i64)->Result<Self>{
+
+        let current = load_current_index_checked(sysvar_instructions)?;
+        require!(current > 0,ErrorCode::MissingOdometerReading);
+
+        let ix = load_instruction_at_checked(current as usize - 1,sysvar_instructions)?;
+        require_keys_eq!(ix.program_id,ed25519_program::ID,ErrorCode::MissingOdometerReading);
+
+        let (signer,message) = Self::signed_message(&ix.data).ok_or(ErrorCode::InvalidOdometerReading)?;
+        require!(signer == device.as_ref(),ErrorCode::InvalidOdometerReading);
+
+        let reading = Self::parse(message,rental_state).ok_or(ErrorCode::InvalidOdometerReading)?;
+        let age = now.checked_sub(reading.read_at).ok_or(ErrorCode::StaleOdometerReading)?;
+        require!((0..=MAX_ODOMETER_READING_AGE).contains(&age),ErrorCode::StaleOdometerReading);
+
+        Ok(reading)
+    }
+
+    //the single signature's public key and message, both required to live in the ed25519 instruction itself
+    fn signed_message(data:&[u8])->Option<(&[u8],&[u8])>{
+
+        if data.len() < ED25519_HEADER + ED25519_OFFSETS || data[0] != 1 {
+            return None;
+        }
+
+        let offsets:Vec<u16> = data[ED25519_HEADER..ED25519_HEADER + ED25519_OFFSETS]
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0],bytes[1]]))
+            .collect();
+        let [_,signature_ix,public_key_offset,public_key_ix,message_offset,message_size,message_ix] = offsets[..] else {
+            return None;
+        };
+
+        if [signature_ix,public_key_ix,message_ix].iter().any(|index| *index != u16::MAX) {
+            return None;
+        }
+
+        let public_key = data.get(public_key_offset as usize..public_key_offset as usize + 32)?;
+        let message = data.get(message_offset as usize..message_offset as usize + message_size as usize)?;
+
+        Some((public_key,message))
+    }
+
+    fn parse(message:&[u8],rental_state:&Pubkey)->Option<Self>{
+
+        let body = message.strip_prefix(ODOMETER_READING_PREFIX)?;
+        let body = body.strip_prefix(rental_state.as_ref())?;
+        if body.len() != 16 {
+            return None;
+        }
+
+        Some(Self{
+            odometer_km:u64::from_le_bytes(body[..8].try_into().ok()?),
+            read_at:i64::from_le_bytes(body[8..].try_into().ok()?),
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, errors::ErrorCode, state::{PriceFeed, PriceSource}};
+
+pub const PYTH_RECEIVER_PROGRAM_ID:Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+const PRICE_UPDATE_V2_DISCRIMINATOR:[u8;8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+//pyth receiver's PriceUpdateV2, only fully verified updates are accepted
+#[derive(AnchorDeserialize)]
+struct PythPriceUpdate{
+    _write_authority:Pubkey,
+    verification_level:PythVerificationLevel,
+    feed_id:[u8;32],
+    price:i64,
+    conf:u64,
+    exponent:i32,
+    publish_time:i64,
+}
+
+#[derive(AnchorDeserialize,PartialEq)]
+enum PythVerificationLevel{
+    Partial{_num_signatures:u8},
+    Full,
+}
+
+//layout of a mock feed account, no discriminator so tests can write it directly
+#[derive(AnchorSerialize,AnchorDeserialize)]
+pub struct MockPrice{
+    pub price:i64,
+    pub conf:u64,
+    pub exponent:i32,
+    pub publish_time:i64,
+}
+
+//price of one whole payment token in the reference currency, `price * 10^exponent`
+pub struct OraclePrice{
+    pub price:i64,
+    pub conf:u64,
+    pub exponent:i32,
+    pub publish_time:i64,
+}
+
+impl OraclePrice{
+
+    pub fn load(feed:&PriceFeed,account:&AccountInfo)->Result<Self>{
+        let data = account.try_borrow_data()?;
+
+        match &feed.source {
+            PriceSource::Pyth{feed_id} => {
+                require_keys_eq!(*account.owner,PYTH_RECEIVER_PROGRAM_ID,ErrorCode::InvalidPriceFeed);
+                require!(data.len() >= 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,ErrorCode::InvalidPriceFeed);
+
+                let update = PythPriceUpdate::deserialize(&mut &data[8..])?;
+                require!(update.verification_level == PythVerificationLevel::Full,ErrorCode::InvalidPriceFeed);
+                require!(update.feed_id == *feed_id,ErrorCode::InvalidPriceFeed);
+
+                Ok(OraclePrice{price:update.price,conf:update.conf,exponent:update.exponent,publish_time:update.publish_time})
+            },
+            PriceSource::Mock{account:mock} => {
+                require_keys_eq!(account.key(),*mock,ErrorCode::InvalidPriceFeed);
+
+                let mock = MockPrice::deserialize(&mut &data[..])?;
+
+                Ok(OraclePrice{price:mock.price,conf:mock.conf,exponent:mock.exponent,publish_time:mock.publish_time})
+            },
+        }
+    }
+
+    pub fn check(&self,feed:&PriceFeed,now:i64)->Result<()>{
+        require!(self.price > 0,ErrorCode::InvalidPriceFeed);
+        require!(now.saturating_sub(self.publish_time) <= feed.max_staleness,ErrorCode::StalePrice);
+
+        let max_conf = (self.price as u128)
+            .checked_mul(feed.max_confidence_bps as u128)
+            .and_then(|conf| conf.checked_div(BPS_DENOMINATOR as u128))
+            .ok_or(ErrorCode::ValueOverflow)?;
+        require!(self.conf as u128 <= max_conf,ErrorCode::PriceConfidenceTooWide);
+
+        Ok(())
+    }
+
+    //converts a REFERENCE_DECIMALS amount into payment token base units, rounding in the owner's favour
+    pub fn to_token_amount(&self,reference_amount:u64,decimals:u8)->Result<u64>{
+        let scale = self.exponent.checked_add(REFERENCE_DECIMALS as i32).ok_or(ErrorCode::ValueOverflow)?;
+
+        let mut numerator = (reference_amount as u128).checked_mul(10u128.checked_pow(decimals as u32).ok_or(ErrorCode::ValueOverflow)?);
+        let mut denominator = Some(self.price as u128);
+
+        if scale >= 0 {
+            denominator = denominator.and_then(|price| price.checked_mul(10u128.checked_pow(scale as u32)?));
+        } else {
+            numerator = numerator.and_then(|amount| amount.checked_mul(10u128.checked_pow(scale.unsigned_abs())?));
+        }
+
+        let (Some(numerator),Some(denominator)) = (numerator,denominator) else {
+            return err!(ErrorCode::ValueOverflow);
+        };
+
+        u64::try_from(numerator.div_ceil(denominator)).map_err(|_| ErrorCode::ValueOverflow.into())
+    }
+}
@@ -0,0 +1,306 @@
+use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
+use anchor_spl::token_interface::{Mint, TokenAccount, TransferChecked};
+
+use crate::constants::BPS_DENOMINATOR;
+
+use crate::{state::*, errors::ErrorCode, utils::{amount_before_fee, transfer_checked_with_hook}};
+
+//one code path for moving rent and deposit, whether a listing is priced in an SPL token or in lamports
+pub struct Escrow<'a,'info>{
+    pub vault:AccountInfo<'info>,
+    pub authority:AccountInfo<'info>,
+    pub signer_seeds:Vec<Vec<u8>>,
+    pub rail:PaymentRail<'a,'info>,
+}
+
+pub enum PaymentRail<'a,'info>{
+    Token{
+        token_program:AccountInfo<'info>,
+        mint:AccountInfo<'info>,
+        decimals:u8,
+        remaining_accounts:&'a [AccountInfo<'info>],
+    },
+    Lamports{
+        system_program:AccountInfo<'info>,
+    },
+}
+
+impl<'a,'info> Escrow<'a,'info>{
+
+    //token escrow is an ATA owned by rental_state, once a renter picks a payment mint no other mint can settle
+    pub fn token(token_program:AccountInfo<'info>,mint:&InterfaceAccount<'info,Mint>,vault:AccountInfo<'info>,rental_state:&Account<'info,RentalState>,remaining_accounts:&'a [AccountInfo<'info>])->Result<Self>{
+        if let Some(payment_mint) = rental_state.payment_mint {
+            require_keys_eq!(payment_mint,mint.key(),ErrorCode::InvalidPaymentMint);
+        }
+
+        Ok(Escrow{
+            vault,
+            authority:rental_state.to_account_info(),
+            signer_seeds:rental_state.signer_seeds(),
+            rail:PaymentRail::Token{
+                token_program,
+                mint:mint.to_account_info(),
+                decimals:mint.decimals,
+                remaining_accounts,
+            },
+        })
+    }
+
+    //lamport escrow is a system account PDA that signs for itself
+    pub fn lamports(system_program:AccountInfo<'info>,vault:AccountInfo<'info>,rental_state:&Account<'info,RentalState>,vault_bump:u8)->Self{
+        Escrow{
+            authority:vault.clone(),
+            vault,
+            signer_seeds:vec![b"sol_vault".to_vec(),rental_state.key().to_bytes().to_vec(),vec![vault_bump]],
+            rail:PaymentRail::Lamports{system_program},
+        }
+    }
+
+    //picks the rail the listing settles on, the other rail's accounts are left out by the caller
+    pub fn for_listing(rental_state:&Account<'info,RentalState>,token_program:AccountInfo<'info>,system_program:AccountInfo<'info>,rent_fee_mint:Option<&InterfaceAccount<'info,Mint>>,rent_vault:Option<AccountInfo<'info>>,sol_vault:Option<AccountInfo<'info>>,remaining_accounts:&'a [AccountInfo<'info>])->Result<Self>{
+        match rental_state.payment_kind {
+            PaymentKind::Token => {
+                let (Some(rent_fee_mint),Some(rent_vault)) = (rent_fee_mint,rent_vault) else {
+                    return err!(ErrorCode::MissingPaymentAccounts);
+                };
+                Escrow::token(token_program,rent_fee_mint,rent_vault,rental_state,remaining_accounts)
+            },
+            PaymentKind::Lamports => {
+                let (Some(sol_vault),Some(vault_bump)) = (sol_vault,rental_state.sol_vault_bump) else {
+                    return err!(ErrorCode::MissingPaymentAccounts);
+                };
+                Ok(Escrow::lamports(system_program,sol_vault,rental_state,vault_bump))
+            },
+        }
+    }
+
+    //token rails pay into the party's token account, lamport rails straight into the wallet
+    pub fn payee(&self,token_account:Option<AccountInfo<'info>>,wallet:AccountInfo<'info>)->Result<AccountInfo<'info>>{
+        match self.rail {
+            PaymentRail::Token{..} => token_account.ok_or(ErrorCode::MissingPaymentAccounts.into()),
+            PaymentRail::Lamports{..} => Ok(wallet),
+        }
+    }
+
+    pub fn balance(&self)->Result<u64>{
+        match &self.rail {
+            PaymentRail::Token{..} => {
+                let data = self.vault.try_borrow_data()?;
+                Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+            },
+            PaymentRail::Lamports{..} => Ok(self.vault.lamports()),
+        }
+    }
+
+    //amount to send so that `received` lands after any token-2022 transfer fee
+    pub fn amount_before_fee(&self,received:u64)->Result<u64>{
+        match &self.rail {
+            PaymentRail::Token{mint,..} => amount_before_fee(mint,received),
+            PaymentRail::Lamports{..} => Ok(received),
+        }
+    }
+
+    //`from` is the payer's token account, or the payer's wallet for lamports
+    pub fn collect(&self,from:AccountInfo<'info>,payer:AccountInfo<'info>,amount:u64)->Result<()>{
+        match &self.rail {
+            PaymentRail::Token{token_program,mint,decimals,remaining_accounts} => {
+                let cpi_accounts = TransferChecked{
+                    from,
+                    to:self.vault.clone(),
+                    mint:mint.clone(),
+                    authority:payer
+                };
+
+                let ctx = CpiContext::new(token_program.clone(),cpi_accounts).with_remaining_accounts(remaining_accounts.to_vec());
+
+                transfer_checked_with_hook(ctx, amount,*decimals)
+            },
+            PaymentRail::Lamports{system_program} => {
+                let cpi_accounts = Transfer{
+                    from:payer,
+                    to:self.vault.clone(),
+                };
+
+                let ctx = CpiContext::new(system_program.clone(),cpi_accounts);
+
+                transfer(ctx, amount)
+            },
+        }
+    }
+
+    //`to` is the payee's token account, or the payee's wallet for lamports
+    pub fn pay_out(&self,to:AccountInfo<'info>,amount:u64)->Result<()>{
+        let seeds:Vec<&[u8]> = self.signer_seeds.iter().map(|seed| seed.as_slice()).collect();
+        let signer_seed = &[&seeds[..]];
+
+        match &self.rail {
+            PaymentRail::Token{token_program,mint,decimals,remaining_accounts} => {
+                let cpi_accounts = TransferChecked{
+                    from:self.vault.clone(),
+                    to,
+                    mint:mint.clone(),
+                    authority:self.authority.clone()
+                };
+
+                let ctx = CpiContext::new_with_signer(token_program.clone(),cpi_accounts,signer_seed).with_remaining_accounts(remaining_accounts.to_vec());
+
+                transfer_checked_with_hook(ctx, amount,*decimals)
+            },
+            PaymentRail::Lamports{system_program} => {
+                let cpi_accounts = Transfer{
+                    from:self.vault.clone(),
+                    to,
+                };
+
+                let ctx = CpiContext::new_with_signer(system_program.clone(),cpi_accounts,signer_seed);
+
+                transfer(ctx, amount)
+            },
+        }
+    }
+
+    //splits by share in listing order, the last payee takes the rounding remainder
+    pub fn pay_split(&self,payees:&[Payee],accounts:&[AccountInfo<'info>],amount:u64)->Result<()>{
+        require!(accounts.len() == payees.len(),ErrorCode::InvalidPayeeAccount);
+
+        let mut remaining = amount;
+
+        for (i,(payee,account)) in payees.iter().zip(accounts).enumerate(){
+            self.check_payee(payee,account)?;
+
+            let share = if i == payees.len() - 1 {
+                remaining
+            } else {
+                u64::try_from((amount as u128) * (payee.share_bps as u128) / (BPS_DENOMINATOR as u128)).map_err(|_| ErrorCode::ValueOverflow)?
+            };
+            remaining = remaining.checked_sub(share).ok_or(ErrorCode::ValueOverflow)?;
+
+            if share > 0 {
+                self.pay_out(account.clone(),share)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    //token payees are paid into their own account for the escrow mint, lamport payees straight into the wallet
+    fn check_payee(&self,payee:&Payee,account:&AccountInfo<'info>)->Result<()>{
+        match &self.rail {
+            PaymentRail::Token{token_program,mint,..} => {
+                require_keys_eq!(*account.owner,token_program.key(),ErrorCode::InvalidPayeeAccount);
+                let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+                require!(token_account.owner == payee.wallet && token_account.mint == mint.key(),ErrorCode::InvalidPayeeAccount);
+            },
+            PaymentRail::Lamports{..} => require_keys_eq!(account.key(),payee.wallet,ErrorCode::InvalidPayeeAccount),
+        }
+        Ok(())
+    }
+
+    //takes the quoted rent and deposit from the renter and returns what actually arrived, as (rent, deposit)
+    pub fn collect_rental(&self,rental_state:&RentalState,quote:(u64,u64),source:AccountInfo<'info>,renter:AccountInfo<'info>,renter_balance:u64)->Result<(u64,u64)>{
+
+        let (rent_fee,deposit_fee) = quote;
+
+        //when the renter pays transfer fees, both legs are grossed up so the quoted amounts arrive in full
+        let (rent_amount,deposit_amount) = match rental_state.transfer_fee_payer {
+            TransferFeePayer::Renter => (
+                self.amount_before_fee(rent_fee)?,
+                self.amount_before_fee(deposit_fee)?,
+            ),
+            TransferFeePayer::Owner => (rent_fee,deposit_fee),
+        };
+
+        let total_fee = rent_amount.checked_add(deposit_amount).ok_or(ErrorCode::ValueOverflow)?;
+
+        require!(renter_balance>=total_fee,ErrorCode::InsufficientFunds);
+
+        let vault_balance_before = self.balance()?;
+
+        //getting rent fee
+        self.collect(source.clone(),renter.clone(),rent_amount)?;
+
+        //getting deposit fee
+        self.collect(source,renter,deposit_amount)?;
+
+        //escrow what actually arrived, booking the deposit first so any shortfall comes out of the rent
+        let received = self.balance()?.checked_sub(vault_balance_before).ok_or(ErrorCode::ValueOverflow)?;
+        let escrowed_deposit = received.min(deposit_fee);
+
+        Ok((received - escrowed_deposit,escrowed_deposit))
+    }
+
+    //grosses up the leg owed to the party that does not pay transfer fees, funded from the payer's leg
+    pub fn split_transfer_fees(&self,transfer_fee_payer:TransferFeePayer,owner_rent:u64,deposit_refund:u64)->Result<(u64,u64)>{
+
+        match transfer_fee_payer {
+            TransferFeePayer::Renter => {
+                let fee = self.amount_before_fee(owner_rent)?.checked_sub(owner_rent).ok_or(ErrorCode::ValueOverflow)?;
+                let fee = fee.min(deposit_refund);
+                Ok((owner_rent + fee,deposit_refund - fee))
+            },
+            TransferFeePayer::Owner => {
+                let fee = self.amount_before_fee(deposit_refund)?.checked_sub(deposit_refund).ok_or(ErrorCode::ValueOverflow)?;
+                let fee = fee.min(owner_rent);
+                Ok((owner_rent - fee,deposit_refund + fee))
+            },
+        }
+    }
+
+    //the listing's own referral share wins over the protocol default
+    pub fn pay_referrer(&self,rental_state:&RentalState,config:&Config,referrer_stats:Option<&mut ReferrerStats>,referrer_fee_ata:Option<&InterfaceAccount<'info,TokenAccount>>,referrer_wallet:Option<AccountInfo<'info>>)->Result<u64>{
+
+        let Some(referrer) = rental_state.referrer else {
+            return Ok(0);
+        };
+
+        let referral_fee_bps = rental_state.referral_fee_bps.unwrap_or(config.referral_fee_bps);
+        let referral_fee = bps_share(rental_state.escrowed_rent,referral_fee_bps).ok_or(ErrorCode::ValueOverflow)?;
+
+        let Some(referrer_stats) = referrer_stats else {
+            return err!(ErrorCode::InvalidReferrer);
+        };
+        require_keys_eq!(referrer_stats.referrer,referrer,ErrorCode::InvalidReferrer);
+
+        let referrer_payee = match (&self.rail,referrer_fee_ata,referrer_wallet) {
+            (PaymentRail::Token{..},Some(referrer_fee_ata),_) if referrer_fee_ata.owner == referrer => referrer_fee_ata.to_account_info(),
+            (PaymentRail::Lamports{..},_,Some(referrer_wallet)) if referrer_wallet.key() == referrer => referrer_wallet,
+            _ => return err!(ErrorCode::InvalidReferrer),
+        };
+
+        if referral_fee > 0 {
+            self.pay_out(referrer_payee,referral_fee)?;
+        }
+
+        referrer_stats.record_settled(referral_fee)?;
+
+        Ok(referral_fee)
+    }
+}
+
+//seeds a lamport vault with its rent-exempt minimum so partial payouts can never strand it below that
+pub fn fund_sol_vault<'info>(system_program:AccountInfo<'info>,payer:AccountInfo<'info>,sol_vault:Option<AccountInfo<'info>>)->Result<()>{
+
+    let sol_vault = sol_vault.ok_or(ErrorCode::MissingPaymentAccounts)?;
+
+    let required = Rent::get()?.minimum_balance(0);
+    let current = sol_vault.lamports();
+
+    if required > current {
+        let cpi_accounts = Transfer{
+            from:payer,
+            to:sol_vault,
+        };
+
+        let ctx = CpiContext::new(system_program,cpi_accounts);
+
+        transfer(ctx, required - current)?;
+    }
+
+    Ok(())
+}
+
+//payee accounts sit at the front of remaining accounts in listing order, what is left goes to transfer hooks
+pub fn split_payee_accounts<'a,'info>(rental_state:&RentalState,remaining_accounts:&'a [AccountInfo<'info>])->Result<(&'a [AccountInfo<'info>],&'a [AccountInfo<'info>])>{
+    require!(remaining_accounts.len() >= rental_state.payees.len(),ErrorCode::InvalidPayeeAccount);
+    Ok(remaining_accounts.split_at(rental_state.payees.len()))
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, Create},
+    token_2022::{
+        burn, close_account, initialize_mint2, mint_to, Burn, CloseAccount, InitializeMint2, MintTo,
+        spl_token_2022::{extension::ExtensionType, state::Mint},
+    },
+    token_interface::{
+        metadata_pointer_initialize, mint_close_authority_initialize, non_transferable_mint_initialize,
+        permanent_delegate_initialize, token_metadata_initialize, token_metadata_update_field,
+        MetadataPointerInitialize, MintCloseAuthorityInitialize, NonTransferableMintInitialize,
+        PermanentDelegateInitialize, TokenMetadataInitialize, TokenMetadataUpdateField,
+        spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::{Field, TokenMetadata},
+    },
+};
+
+use crate::{constants::*, errors::ErrorCode, utils::create_pda_account};
+
+//non-transferable token-2022 proof of who holds the car, minted by the rental_state for one rental
+pub struct RentalPass<'info>{
+    pub token_program:AccountInfo<'info>,
+    pub mint:AccountInfo<'info>,
+    pub renter_pass:AccountInfo<'info>,
+    pub renter:AccountInfo<'info>,
+    pub rental_state:AccountInfo<'info>,
+    pub system_program:AccountInfo<'info>,
+    pub associated_token_program:AccountInfo<'info>,
+}
+
+impl<'info> RentalPass<'info>{
+
+    pub fn new(token_program:AccountInfo<'info>,mint:AccountInfo<'info>,renter_pass:AccountInfo<'info>,renter:AccountInfo<'info>,rental_state:AccountInfo<'info>,system_program:AccountInfo<'info>,associated_token_program:AccountInfo<'info>)->Self{
+        RentalPass{
+            token_program,
+            mint,
+            renter_pass,
+            renter,
+            rental_state,
+            system_program,
+            associated_token_program,
+        }
+    }
+
+    //the rental_state is mint, close and metadata authority, and permanent delegate so any settlement path can burn
+    pub fn issue(&self,car:&Pubkey,start:i64,end:i64,mint_bump:u8,rental_seeds:&[&[&[u8]]])->Result<()>{
+
+        let rental_state = self.rental_state.key();
+        let mint_seeds:&[&[u8]] = &[b"rental_pass", rental_state.as_ref(), &[mint_bump]];
+
+        let fields = vec![
+            ("rental_state".to_string(),rental_state.to_string()),
+            ("car".to_string(),car.to_string()),
+            ("start".to_string(),start.to_string()),
+            ("end".to_string(),end.to_string()),
+        ];
+
+        let metadata = TokenMetadata{
+            update_authority:OptionalNonZeroPubkey(rental_state),
+            mint:self.mint.key(),
+            name:RENTAL_PASS_NAME.to_string(),
+            symbol:RENTAL_PASS_SYMBOL.to_string(),
+            uri:String::new(),
+            additional_metadata:fields.clone(),
+        };
+
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::MetadataPointer,
+            ExtensionType::MintCloseAuthority,
+            ExtensionType::PermanentDelegate,
+        ])?;
+        let metadata_space = metadata.tlv_size_of()?;
+
+        //token-2022 reallocs the mint for the metadata without topping it up, so it is funded for both here
+        let lamports = Rent::get()?.minimum_balance(space.checked_add(metadata_space).ok_or(ErrorCode::ValueOverflow)?);
+
+        create_pda_account(
+            self.renter.clone(),
+            self.mint.clone(),
+            self.system_program.clone(),
+            lamports,
+            space as u64,
+            self.token_program.key,
+            mint_seeds,
+        )?;
+
+        non_transferable_mint_initialize(CpiContext::new(self.token_program.clone(),NonTransferableMintInitialize{
+            token_program_id:self.token_program.clone(),
+            mint:self.mint.clone(),
+        }))?;
+
+        metadata_pointer_initialize(CpiContext::new(self.token_program.clone(),MetadataPointerInitialize{
+            token_program_id:self.token_program.clone(),
+            mint:self.mint.clone(),
+        }),Some(rental_state),Some(self.mint.key()))?;
+
+        mint_close_authority_initialize(CpiContext::new(self.token_program.clone(),MintCloseAuthorityInitialize{
+            token_program_id:self.token_program.clone(),
+            mint:self.mint.clone(),
+        }),Some(&rental_state))?;
+
+        permanent_delegate_initialize(CpiContext::new(self.token_program.clone(),PermanentDelegateInitialize{
+            token_program_id:self.token_program.clone(),
+            mint:self.mint.clone(),
+        }),&rental_state)?;
+
+        initialize_mint2(CpiContext::new(self.token_program.clone(),InitializeMint2{mint:self.mint.clone()}),0,&rental_state,None)?;
+
+        token_metadata_initialize(CpiContext::new_with_signer(self.token_program.clone(),TokenMetadataInitialize{
+            program_id:self.token_program.clone(),
+            metadata:self.mint.clone(),
+            update_authority:self.rental_state.clone(),
+            mint_authority:self.rental_state.clone(),
+            mint:self.mint.clone(),
+        },rental_seeds),metadata.name,metadata.symbol,metadata.uri)?;
+
+        for (key,value) in fields {
+            token_metadata_update_field(CpiContext::new_with_signer(self.token_program.clone(),TokenMetadataUpdateField{
+                program_id:self.token_program.clone(),
+                metadata:self.mint.clone(),
+                update_authority:self.rental_state.clone(),
+            },rental_seeds),Field::Key(key),value)?;
+        }
+
+        //a renter who never closed an earlier pass account gets it reused
+        create_idempotent(CpiContext::new(self.associated_token_program.clone(),Create{
+            payer:self.renter.clone(),
+            associated_token:self.renter_pass.clone(),
+            authority:self.renter.clone(),
+            mint:self.mint.clone(),
+            system_program:self.system_program.clone(),
+            token_program:self.token_program.clone(),
+        }))?;
+
+        mint_to(CpiContext::new_with_signer(self.token_program.clone(),MintTo{
+            mint:self.mint.clone(),
+            to:self.renter_pass.clone(),
+            authority:self.rental_state.clone(),
+        },rental_seeds),1)
+    }
+
+    //burns the pass and hands both accounts' rent back to the renter, rentals started before passes existed have none
+    pub fn revoke(&self,rental_seeds:&[&[&[u8]]])->Result<()>{
+
+        if self.mint.data_is_empty() {
+            return Ok(());
+        }
+
+        burn(CpiContext::new_with_signer(self.token_program.clone(),Burn{
+            mint:self.mint.clone(),
+            from:self.renter_pass.clone(),
+            authority:self.rental_state.clone(),
+        },rental_seeds),1)?;
+
+        //rulings go through without the renter, who can close the empty pass account themselves
+        if self.renter.is_signer {
+            close_account(CpiContext::new(self.token_program.clone(),CloseAccount{
+                account:self.renter_pass.clone(),
+                destination:self.renter.clone(),
+                authority:self.renter.clone(),
+            }))?;
+        }
+
+        close_account(CpiContext::new_with_signer(self.token_program.clone(),CloseAccount{
+            account:self.mint.clone(),
+            destination:self.renter.clone(),
+            authority:self.rental_state.clone(),
+        },rental_seeds))
+    }
+}
@@ -0,0 +1,195 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{constants::*, errors::ErrorCode, state::*, utils::{Ban, Escrow, RentalPass}};
+
+//the custody independent half of ending a rental, every end and exit instruction moves the car back itself and hands the rest here
+pub struct Settlement<'a,'info>{
+    pub rental_state:&'a mut Account<'info,RentalState>,
+    pub owner:AccountInfo<'info>,
+    pub owner_profile:&'a mut Account<'info,UserProfile>,
+    pub owner_profile_bump:u8,
+    pub renter_profile:&'a mut Account<'info,UserProfile>,
+    pub renter_profile_bump:u8,
+    pub pass:RentalPass<'info>,
+    pub escrow:Escrow<'a,'info>,
+    //owner's and renter's payout accounts on the escrow's rail, see Escrow::payee
+    pub owner_payee:AccountInfo<'info>,
+    pub renter_payee:AccountInfo<'info>,
+    //owner side payees in listing order, unused when the listing has none
+    pub payee_accounts:&'a [AccountInfo<'info>],
+}
+
+//where the rent's protocol and referral cuts go
+pub struct Fees<'a,'info>{
+    pub config:&'a Config,
+    //the config's token account for the escrow mint, or the config itself for lamports, see Escrow::payee
+    pub treasury:AccountInfo<'info>,
+    pub referrer_stats:Option<&'a mut ReferrerStats>,
+    pub referrer_fee_ata:Option<&'a InterfaceAccount<'info,TokenAccount>>,
+    pub referrer_wallet:Option<AccountInfo<'info>>,
+}
+
+impl<'a,'info> Settlement<'a,'info>{
+
+    //returned car: records both sides, leaves a receipt, takes the protocol fee, pays the referrer, the deposit back and the rent to the owner side.
+    //`overage` is taken from the deposit on top of the rent, the lamport vault's rent reserve goes to `reserve_recipient`
+    pub fn complete(&mut self,fees:Fees<'_,'info>,receipt:&mut Account<'info,RentalReceipt>,receipt_bump:u8,overage:u64,reserve_recipient:AccountInfo<'info>)->Result<()>{
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let volume = self.rental_state.escrowed_rent;
+        let late = self.rental_state.is_late(now);
+
+        self.owner_profile.init_if_new(self.owner.key(),self.owner_profile_bump);
+        self.owner_profile.record_rental(volume,false)?;
+
+        self.renter_profile.init_if_new(self.pass.renter.key(),self.renter_profile_bump);
+        self.renter_profile.record_rental(volume,late)?;
+
+        receipt.set_inner(RentalReceipt{
+            version:RentalReceipt::VERSION,
+            rental_state:self.rental_state.key(),
+            rental_start_time:self.rental_state.rental_start_time.unwrap_or_default(),
+            owner:self.owner.key(),
+            renter:self.pass.renter.key(),
+            car_nft_mint:self.rental_state.car_nft_mint,
+            ended_at:now,
+            owner_rating:None,
+            renter_rating:None,
+            receipt_bump,
+        });
+
+        self.revoke_pass()?;
+
+        let escrowed_rent = self.rental_state.escrowed_rent;
+        let protocol_fee = fees.config.protocol_fee(escrowed_rent).ok_or(ErrorCode::ValueOverflow)?;
+        let referral_fee = self.escrow.pay_referrer(
+            self.rental_state,
+            fees.config,
+            fees.referrer_stats,
+            fees.referrer_fee_ata,
+            fees.referrer_wallet,
+        )?;
+        let owner_rent = escrowed_rent.checked_sub(protocol_fee).and_then(|rent| rent.checked_sub(referral_fee)).ok_or(ErrorCode::ValueOverflow)?;
+
+        //km past the allowance go to the owner side, never more than the deposit covers
+        let overage = overage.min(self.rental_state.escrowed_deposit);
+        let owner_rent = owner_rent.checked_add(overage).ok_or(ErrorCode::ValueOverflow)?;
+
+        let (owner_rent,deposit_refund) = self.escrow.split_transfer_fees(self.rental_state.transfer_fee_payer,owner_rent,self.rental_state.escrowed_deposit - overage)?;
+
+        //sending deposit to renter
+        self.escrow.pay_out(self.renter_payee.clone(),deposit_refund)?;
+
+        //sending protocol fee to treasury, withdrawn through the multisig
+        if protocol_fee > 0 {
+            self.escrow.pay_out(fees.treasury,protocol_fee)?;
+        }
+
+        //sending rent to owner
+        self.pay_owner(owner_rent)?;
+
+        if self.rental_state.payment_kind == PaymentKind::Lamports {
+            self.escrow.pay_out(reserve_recipient,self.escrow.balance()?)?;
+        }
+
+        self.clear_rental();
+
+        Ok(())
+    }
+
+    //disputed rental: the arbitrator's split of the escrow, a lost dispute on whichever side got less than it put in,
+    //and a ban for renters who forfeit most of their deposit
+    pub fn dispute(&mut self,arbitrator:Pubkey,renter_payout:u64,owner_payout:u64,ban:Ban<'info>,ban_bump:u8,referrer_stats:Option<&mut ReferrerStats>)->Result<()>{
+
+        let total_in_escrow = self.rental_state.escrowed_rent.checked_add(self.rental_state.escrowed_deposit).ok_or(ErrorCode::ValueOverflow)?;
+        require!(renter_payout.checked_add(owner_payout).ok_or(ErrorCode::ValueOverflow)? <= total_in_escrow,ErrorCode::InvalidPayout);
+
+        self.owner_profile.init_if_new(self.owner.key(),self.owner_profile_bump);
+        self.renter_profile.init_if_new(self.pass.renter.key(),self.renter_profile_bump);
+
+        if renter_payout < self.rental_state.escrowed_deposit {
+            self.renter_profile.record_dispute_lost()?;
+        }
+
+        if owner_payout < self.rental_state.escrowed_rent {
+            self.owner_profile.record_dispute_lost()?;
+        }
+
+        let deposit = self.rental_state.escrowed_deposit as u128;
+        let forfeited = deposit.saturating_sub(renter_payout as u128);
+
+        if deposit > 0 && forfeited.checked_mul(BPS_DENOMINATOR as u128).ok_or(ErrorCode::ValueOverflow)? >= deposit.checked_mul(BAN_DISPUTE_FORFEIT_BPS as u128).ok_or(ErrorCode::ValueOverflow)? {
+            ban.ban(self.pass.renter.key(),BanReason::LostDispute,ban_bump)?;
+        }
+
+        self.revoke_pass()?;
+
+        if owner_payout > 0 {
+            self.pay_owner(owner_payout)?;
+        }
+
+        if renter_payout > 0 {
+            self.escrow.pay_out(self.renter_payee.clone(),renter_payout)?;
+        }
+
+        ReferrerStats::record_exit(self.rental_state.referrer,referrer_stats)?;
+
+        self.rental_state.dispute_caller = Some(arbitrator);
+        self.clear_rental();
+
+        Ok(())
+    }
+
+    //overdue rental: the renter defaults and is banned, the owner side takes the whole escrow
+    pub fn overdue_default(&mut self,ban:Ban<'info>,ban_bump:u8,referrer_stats:Option<&mut ReferrerStats>)->Result<()>{
+
+        self.owner_profile.init_if_new(self.owner.key(),self.owner_profile_bump);
+        self.renter_profile.init_if_new(self.pass.renter.key(),self.renter_profile_bump);
+        self.renter_profile.record_overdue_default()?;
+
+        ban.ban(self.pass.renter.key(),BanReason::OverdueDefault,ban_bump)?;
+
+        self.revoke_pass()?;
+
+        let total_in_escrow = self.rental_state.escrowed_rent.checked_add(self.rental_state.escrowed_deposit).ok_or(ErrorCode::ValueOverflow)?;
+        if total_in_escrow > 0 {
+            self.pay_owner(total_in_escrow)?;
+        }
+
+        ReferrerStats::record_exit(self.rental_state.referrer,referrer_stats)?;
+
+        self.clear_rental();
+
+        Ok(())
+    }
+
+    pub fn revoke_pass(&self)->Result<()>{
+
+        let seeds = self.rental_state.signer_seeds();
+        let seeds:Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+
+        self.pass.revoke(&[&seeds[..]])
+    }
+
+    //listings with payees split the owner's side across them, otherwise it all goes to the owner
+    pub fn pay_owner(&self,amount:u64)->Result<()>{
+        if self.rental_state.payees.is_empty() {
+            return self.escrow.pay_out(self.owner_payee.clone(),amount);
+        }
+
+        self.escrow.pay_split(&self.rental_state.payees,self.payee_accounts,amount)
+    }
+
+    pub fn clear_rental(&mut self){
+        self.rental_state.rental_duration = None;
+        self.rental_state.start_odometer = None;
+        self.rental_state.renter = None;
+        self.rental_state.referrer = None;
+        self.rental_state.escrowed_rent = 0;
+        self.rental_state.escrowed_deposit = 0;
+        self.rental_state.rented = false;
+        self.rental_state.status = StatusData::Finished;
+    }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+
+use crate::errors::ErrorCode;
+
+//amount to send so that `received` arrives after the mint takes its fee
+pub fn amount_before_fee(mint:&AccountInfo,received:u64)->Result<u64>{
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let Ok(fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(received);
+    };
+
+    let epoch = Clock::get()?.epoch;
+    fee_config.get_epoch_fee(epoch).calculate_pre_fee_amount(received).ok_or(ErrorCode::ValueOverflow.into())
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::onchain::invoke_transfer_checked,
+    token_interface::TransferChecked,
+};
+
+//drop-in for token_interface::transfer_checked that also resolves the mint's transfer-hook
+//extra accounts out of the context's remaining accounts, a no-op for mints without a hook
+pub fn transfer_checked_with_hook<'info>(ctx:CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,amount:u64,decimals:u8)->Result<()>{
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )?;
+
+    Ok(())
+}
@@ -2,6 +2,30 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Rental } from "../target/types/rental";
 import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import {
+  createTree,
+  fetchMerkleTree,
+  findLeafAssetIdPda,
+  findTreeConfigPda,
+  getCurrentRoot,
+  getMerkleRoot,
+  hashLeaf,
+  mintToCollectionV1,
+  mplBubblegum,
+  MPL_BUBBLEGUM_PROGRAM_ID,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  TokenProgramVersion,
+  TokenStandard,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  create as createCoreAsset,
+  createCollection as createCoreCollection,
+  fetchAsset,
+  fetchCollection,
+  MPL_CORE_PROGRAM_ID,
+  mplCore,
+} from "@metaplex-foundation/mpl-core";
 import {
   createSignerFromKeypair,
   generateSigner,
@@ -13,22 +37,34 @@ import {
 import { Keypair, LAMPORTS_PER_SOL, PublicKey, Signer } from "@solana/web3.js";
 import {
   createNft,
+  createProgrammableNft,
   findMasterEditionPda,
   findMetadataPda,
+  findTokenRecordPda,
   Key,
   MPL_TOKEN_METADATA_PROGRAM_ID,
   mplTokenMetadata,
+  verifyCollectionV1,
   verifySizedCollectionItem,
 } from "@metaplex-foundation/mpl-token-metadata";
 import {
+  createInitializeMintInstruction,
+  createInitializeTransferHookInstruction,
+  createInitializeTransferFeeConfigInstruction,
   createMint,
+  createTransferInstruction,
+  ExtensionType,
   getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  updateTransferHook,
 } from "@solana/spl-token";
 import { expect } from "chai";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { SYSTEM_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/native/system";
@@ -37,6 +73,8 @@ const provider = anchor.AnchorProvider.env();
 const RENT_FEE = new anchor.BN(5);
 const DEPOSIT_FEE = new anchor.BN(4);
 const RENTAL_DURATION = 300;
+const PROTOCOL_FEE_BPS = 0;
+const CONFIG_TIMELOCK = 24 * 60 * 60;
 //add your private key or import from wallet.json
 const PRIVATE_KEY = [
  
@@ -132,6 +170,15 @@ describe("rental", async () => {
   let malicious_user: any;
 
   let rental_state: anchor.web3.PublicKey;
+  let rental_pass_mint: anchor.web3.PublicKey;
+  let renter_pass: anchor.web3.PublicKey;
+  let owner_profile: anchor.web3.PublicKey;
+  let renter_profile: anchor.web3.PublicKey;
+  let ban_entry: anchor.web3.PublicKey;
+  let config: anchor.web3.PublicKey;
+  let multisig: anchor.web3.PublicKey;
+  let programData: anchor.web3.PublicKey;
+  let treasury_ata: anchor.web3.PublicKey;
 
   let vault_ata: any;
   // let vault_ata: anchor.web3.PublicKey;
@@ -157,6 +204,58 @@ describe("rental", async () => {
 
       arbitrator = anchor.web3.Keypair.generate();
 
+      multisig = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig")],
+        program.programId
+      )[0];
+
+      //the protocol singletons are created by the program's upgrade authority, the test wallet deploys it
+      programData = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      )[0];
+
+      let existing_multisig = await program.account.multisig.fetchNullable(
+        multisig
+      );
+      if (!existing_multisig) {
+        await program.methods
+          .initializeMultisig([provider.wallet.publicKey], 1)
+          .accountsStrict({
+            payer: provider.wallet.publicKey,
+            program: program.programId,
+            programData,
+            multisig,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+
+      config = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        program.programId
+      )[0];
+
+      let existing_config = await program.account.config.fetchNullable(config);
+      if (!existing_config) {
+        await program.methods
+          .initializeConfig({
+            arbitrator: arbitrator.publicKey,
+            protocolFeeBps: PROTOCOL_FEE_BPS,
+            referralFeeBps: 0,
+            timelock: new anchor.BN(CONFIG_TIMELOCK),
+          })
+          .accountsStrict({
+            payer: provider.wallet.publicKey,
+            program: program.programId,
+            programData,
+            multisig,
+            config,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+
       rent_fee_mint = await createMint(
         connection,
         convert_keypair_to_anchor_compatiable(paySigner),
@@ -244,6 +343,42 @@ describe("rental", async () => {
         program.programId
       )[0];
 
+      rental_pass_mint = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("rental_pass"), rental_state.toBuffer()],
+        program.programId
+      )[0];
+
+      renter_pass = getAssociatedTokenAddressSync(
+        rental_pass_mint,
+        new anchor.web3.PublicKey(renter.publicKey),
+        false,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      owner_profile = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("profile"),
+          new anchor.web3.PublicKey(owner.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      renter_profile = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("profile"),
+          new anchor.web3.PublicKey(renter.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      ban_entry = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("ban"),
+          new anchor.web3.PublicKey(renter.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+
       // console.log("renter ata created", renter_ata);
 
       malicious_user_pda = anchor.web3.PublicKey.findProgramAddressSync(
@@ -307,6 +442,16 @@ describe("rental", async () => {
 
       owner_fee_ata = create_owner_fee_ata.address;
 
+      treasury_ata = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          provider.wallet.payer,
+          new anchor.web3.PublicKey(rent_fee_mint),
+          config,
+          true
+        )
+      ).address;
+
       // console.log("owner fee ata", owner_fee_ata);
     } catch (error) {
       console.log(error);
@@ -314,10 +459,495 @@ describe("rental", async () => {
     }
   });
 
+  // creates a proposal from the test wallet, which is the only multisig member
+  const create_proposal = async (action: any) => {
+    const multisig_data = await program.account.multisig.fetch(multisig);
+    const proposal = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("proposal"),
+        multisig.toBuffer(),
+        multisig_data.proposalCount.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    await program.methods
+      .createProposal(action)
+      .accountsStrict({
+        proposer: provider.wallet.publicKey,
+        multisig,
+        proposal,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    return proposal;
+  };
+
+  const default_options = {
+    referralFeeBps: null,
+    transferFeePayer: { renter: {} },
+    paymentKind: { token: {} },
+    custody: { vault: {} },
+    denomination: { token: {} },
+    acceptedPayments: [],
+    depositTiers: [],
+    requiredClaims: 0,
+    allowlist: null,
+    blocklist: null,
+    payees: [],
+  };
+
+  // runs a transaction that has to fail and returns the program error it failed with
+  const expect_error = async (tx: Promise<any>, code: string) => {
+    let error_code;
+    try {
+      await tx;
+    } catch (error) {
+      error_code = error.error?.errorCode?.code;
+    }
+    expect(error_code).to.equal(code);
+  };
+
+  const token_account = (
+    mint: anchor.web3.PublicKey,
+    holder: anchor.web3.PublicKey,
+    token_program = TOKEN_PROGRAM_ID
+  ) =>
+    getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      holder,
+      true,
+      undefined,
+      undefined,
+      token_program
+    ).then((account) => account.address);
+
+  // a funded wallet holding `amount` of `pay_mint`, by default enough for a few rentals
+  const new_renter = async (
+    pay_mint: anchor.web3.PublicKey = new anchor.web3.PublicKey(rent_fee_mint),
+    token_program = TOKEN_PROGRAM_ID,
+    amount = 10 * (RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber())
+  ) => {
+    const wallet = createSignerFromKeypair(umi, generateSigner(umi));
+    const key = new anchor.web3.PublicKey(wallet.publicKey);
+    await transfer_sol(2, key, paySigner);
+
+    const fee_ata = await token_account(pay_mint, key, token_program);
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      pay_mint,
+      fee_ata,
+      provider.wallet.payer,
+      amount,
+      [],
+      undefined,
+      token_program
+    );
+
+    const pda = (seed: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(seed), key.toBuffer()],
+        program.programId
+      )[0];
+
+    return {
+      wallet,
+      key,
+      keypair: convert_keypair_to_anchor_compatiable(wallet),
+      fee_ata,
+      profile: pda("profile"),
+      ban_entry: pda("ban"),
+    };
+  };
+
+  // mints another car into the test collection for the owner, with every account a vault listing of it
+  // paid in `pay_mint` uses. the car and the payment mint share one token program, as the settlement paths expect
+  const new_car = async (
+    pay_mint: anchor.web3.PublicKey = new anchor.web3.PublicKey(rent_fee_mint),
+    token_program = TOKEN_PROGRAM_ID,
+    programmable = false
+  ) => {
+    const mint = generateSigner(umi);
+    await (programmable ? createProgrammableNft : createNft)(umi, {
+      mint,
+      name: "Konessige",
+      symbol: "KO",
+      uri: "https://arweave.net/123",
+      sellerFeeBasisPoints: percentAmount(0),
+      collection: { verified: false, key: collection_mint.publicKey },
+      tokenOwner: owner.publicKey,
+      splTokenProgram: publicKey(token_program.toBase58()),
+    }).sendAndConfirm(umi);
+
+    //the v1 verify covers programmable nfts too, the sized collection keeps its count
+    const metadata = findMetadataPda(umi, { mint: mint.publicKey });
+    await verifyCollectionV1(umi, {
+      metadata,
+      collectionMint: collection_mint.publicKey,
+      authority: paySigner,
+    }).sendAndConfirm(umi);
+
+    const owner_key = new anchor.web3.PublicKey(owner.publicKey);
+    const mint_key = new anchor.web3.PublicKey(mint.publicKey);
+    const state = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rental"), mint_key.toBuffer(), owner_key.toBuffer()],
+      program.programId
+    )[0];
+
+    return {
+      mint: mint_key,
+      pay_mint,
+      token_program,
+      rental_state: state,
+      rental_pass_mint: anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("rental_pass"), state.toBuffer()],
+        program.programId
+      )[0],
+      metadata: new anchor.web3.PublicKey(metadata[0]),
+      master_edition: new anchor.web3.PublicKey(
+        findMasterEditionPda(umi, { mint: mint.publicKey })[0]
+      ),
+      owner_ata: getAssociatedTokenAddressSync(
+        mint_key,
+        owner_key,
+        false,
+        token_program
+      ),
+      vault: await token_account(mint_key, state, token_program),
+      rent_vault: await token_account(pay_mint, state, token_program),
+      owner_fee_ata: await token_account(pay_mint, owner_key, token_program),
+      treasury: await token_account(pay_mint, config, token_program),
+    };
+  };
+
+  const list = async (car: any, options: any = {}, accounts: any = {}) => {
+    await program.methods
+      .listCar(RENT_FEE, DEPOSIT_FEE, { ...default_options, ...options })
+      .accountsStrict({
+        owner: new anchor.web3.PublicKey(owner.publicKey),
+        carNftMint: car.mint,
+        collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+        rentalState: car.rental_state,
+        ownerNftAccount: car.owner_ata,
+        vault: car.vault,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: car.token_program,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        metadata: car.metadata,
+        masterEdition: car.master_edition,
+        ownerTokenRecord: null,
+        vaultTokenRecord: null,
+        authorizationRules: null,
+        authorizationRulesProgram: null,
+        sysvarInstructions: null,
+        metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        rentFeeMint: car.pay_mint,
+        ownerFeeAta: car.owner_fee_ata,
+        solVault: null,
+        ...accounts,
+      })
+      .signers([convert_keypair_to_anchor_compatiable(owner)])
+      .rpc();
+    return car;
+  };
+
+  const list_new_car = async (options: any = {}, accounts: any = {}) =>
+    list(await new_car(), options, accounts);
+
+  const rent_listed_car = (
+    car: any,
+    tenant: any,
+    accounts: any = {},
+    pre: anchor.web3.TransactionInstruction[] = []
+  ) =>
+    program.methods
+      .rentCar(new anchor.BN(RENTAL_DURATION))
+      .accountsStrict({
+        owner: new anchor.web3.PublicKey(owner.publicKey),
+        carNftMint: car.mint,
+        rentVault: car.rent_vault,
+        renter: tenant.key,
+        rentFeeMint: car.pay_mint,
+        renterAta: tenant.fee_ata,
+        rentalState: car.rental_state,
+        referrerStats: null,
+        solVault: null,
+        priceFeed: null,
+        priceUpdate: null,
+        renterProfile: null,
+        banEntry: tenant.ban_entry,
+        attestationIssuer: null,
+        attestation: null,
+        allowlist: null,
+        blocklist: null,
+        sysvarInstructions: null,
+        rentalPassMint: car.rental_pass_mint,
+        renterPass: getAssociatedTokenAddressSync(
+          car.rental_pass_mint,
+          tenant.key,
+          false,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: car.token_program,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        ...accounts,
+      })
+      .preInstructions(pre)
+      .signers([tenant.keypair])
+      .rpc();
+
+  const exit_rental = (
+    car: any,
+    tenant: any,
+    renter_payout: anchor.BN,
+    owner_payout: anchor.BN,
+    accounts: any = {},
+    signers: Signer[] = []
+  ) =>
+    program.methods
+      .emergencyExit(renter_payout, owner_payout)
+      .accountsStrict({
+        arbitrator: arbitrator.publicKey,
+        config,
+        owner: new anchor.web3.PublicKey(owner.publicKey),
+        authority: new anchor.web3.PublicKey(owner.publicKey),
+        operatorGrant: null,
+        renter: tenant.key,
+        collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+        fleet: null,
+        carNftMint: car.mint,
+        rentFeeMint: car.pay_mint,
+        rentalState: car.rental_state,
+        rentVault: car.rent_vault,
+        solVault: null,
+        ownerProfile: owner_profile,
+        renterProfile: tenant.profile,
+        referrerStats: null,
+        banEntry: tenant.ban_entry,
+        rentalPassMint: car.rental_pass_mint,
+        renterPass: getAssociatedTokenAddressSync(
+          car.rental_pass_mint,
+          tenant.key,
+          false,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        vault: car.vault,
+        metadata: car.metadata,
+        masterEdition: car.master_edition,
+        ownerTokenRecord: null,
+        vaultTokenRecord: null,
+        authorizationRules: null,
+        authorizationRulesProgram: null,
+        sysvarInstructions: null,
+        renterAta: tenant.fee_ata,
+        ownerAta: car.owner_ata,
+        ownerFeeAta: car.owner_fee_ata,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: car.token_program,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        ...accounts,
+      })
+      .signers([convert_keypair_to_anchor_compatiable(owner), arbitrator, ...signers])
+      .rpc();
+
+  // receipt the current rental of `rental_state` settles into
+  const receipt_of = async (rental_state: anchor.web3.PublicKey) => {
+    const state = await program.account.rentalState.fetch(rental_state);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("receipt"),
+        rental_state.toBuffer(),
+        state.rentalStartTime.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  };
+
+  // ends a rental made through rent_listed_car, payee token accounts go in `payees`
+  const return_car = async (
+    car: any,
+    tenant: any,
+    accounts: any = {},
+    payees: anchor.web3.PublicKey[] = [],
+    pre: anchor.web3.TransactionInstruction[] = [],
+    signers: Signer[] = []
+  ) => {
+    const receipt = await receipt_of(car.rental_state);
+
+    return program.methods
+      .endRental()
+      .accountsStrict({
+        owner: new anchor.web3.PublicKey(owner.publicKey),
+        authority: new anchor.web3.PublicKey(owner.publicKey),
+        operatorGrant: null,
+        renter: tenant.key,
+        collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+        carNftMint: car.mint,
+        rentFeeMint: car.pay_mint,
+        rentalState: car.rental_state,
+        config,
+        treasury: car.treasury,
+        referrerStats: null,
+        referrerFeeAta: null,
+        referrer: null,
+        solVault: null,
+        ownerProfile: owner_profile,
+        renterProfile: tenant.profile,
+        receipt,
+        rentalPassMint: car.rental_pass_mint,
+        renterPass: getAssociatedTokenAddressSync(
+          car.rental_pass_mint,
+          tenant.key,
+          false,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        rentVault: car.rent_vault,
+        vault: car.vault,
+        metadata: car.metadata,
+        masterEdition: car.master_edition,
+        ownerTokenRecord: null,
+        vaultTokenRecord: null,
+        authorizationRules: null,
+        authorizationRulesProgram: null,
+        sysvarInstructions: null,
+        renterAta: tenant.fee_ata,
+        ownerAta: car.owner_ata,
+        ownerFeeAta: car.owner_fee_ata,
+        systemProgram: SYSTEM_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: car.token_program,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        ...accounts,
+      })
+      .remainingAccounts(
+        payees.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .preInstructions(pre)
+      .signers([
+        tenant.keypair,
+        convert_keypair_to_anchor_compatiable(owner),
+        ...signers,
+      ])
+      .rpc();
+  };
+
+  // a token-2022 payment mint carrying one extension, minted by the test wallet
+  const create_token_2022_mint = async (
+    extension: ExtensionType,
+    init_extension: (
+      mint: anchor.web3.PublicKey
+    ) => anchor.web3.TransactionInstruction
+  ) => {
+    const mint = anchor.web3.Keypair.generate();
+    const space = getMintLen([extension]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space,
+          lamports: await connection.getMinimumBalanceForRentExemption(space),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        init_extension(mint.publicKey),
+        createInitializeMintInstruction(
+          mint.publicKey,
+          0,
+          provider.wallet.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [mint]
+    );
+    return mint.publicKey;
+  };
+
+  const balance = async (
+    account: anchor.web3.PublicKey,
+    token_program = TOKEN_PROGRAM_ID
+  ) =>
+    Number(
+      (await getAccount(provider.connection, account, undefined, token_program))
+        .amount
+    );
+
+  describe("Config timelock", async () => {
+    it("Pending change cannot be applied before the timelock", async () => {
+      const proposal = await create_proposal({
+        proposeConfig: {
+          params: {
+            arbitrator: arbitrator.publicKey,
+            protocolFeeBps: PROTOCOL_FEE_BPS + 100,
+            referralFeeBps: 0,
+            timelock: new anchor.BN(CONFIG_TIMELOCK),
+          },
+        },
+      });
+
+      await program.methods
+        .proposeConfig()
+        .accountsStrict({
+          multisig,
+          proposal,
+          config,
+        })
+        .rpc();
+
+      let failed = false;
+      try {
+        await program.methods.applyConfig().accountsStrict({ config }).rpc();
+      } catch (error) {
+        failed = true;
+      }
+      expect(failed).to.equal(true);
+    });
+
+    it("Multisig can cancel a pending change", async () => {
+      const proposal = await create_proposal({ cancelConfig: {} });
+
+      await program.methods
+        .cancelConfig()
+        .accountsStrict({
+          multisig,
+          proposal,
+          config,
+        })
+        .rpc();
+
+      const config_data = await program.account.config.fetch(config);
+      expect(config_data.pending).to.equal(null);
+      expect(config_data.protocolFeeBps).to.equal(PROTOCOL_FEE_BPS);
+    });
+  });
+
   describe("List cars", async () => {
     before(async () => {
       let tx = await program.methods
-        .listCar(RENT_FEE, DEPOSIT_FEE)
+        .listCar(RENT_FEE, DEPOSIT_FEE, {
+          referralFeeBps: null,
+          transferFeePayer: { renter: {} },
+          paymentKind: { token: {} },
+          custody: { vault: {} },
+          denomination: { token: {} },
+          acceptedPayments: [],
+          depositTiers: [],
+          requiredClaims: 0,
+          allowlist: null,
+          blocklist: null,
+          payees: [],
+        })
         .accountsStrict({
           owner: new anchor.web3.PublicKey(owner.publicKey),
           carNftMint: new anchor.web3.PublicKey(car_nft_mint.publicKey),
@@ -330,9 +960,15 @@ describe("rental", async () => {
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           metadata: new anchor.web3.PublicKey(nftmetadata[0]),
           masterEdition: masterEditionPda[0],
+          ownerTokenRecord: null,
+          vaultTokenRecord: null,
+          authorizationRules: null,
+          authorizationRulesProgram: null,
+          sysvarInstructions: null,
           metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
           rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
           ownerFeeAta: new anchor.web3.PublicKey(owner_fee_ata),
+          solVault: null,
         })
         .signers([convert_keypair_to_anchor_compatiable(owner)])
         .rpc();
@@ -349,6 +985,7 @@ describe("rental", async () => {
         expect(state_data.owner.toString()).to.equal(
           new anchor.web3.PublicKey(owner.publicKey).toString()
         );
+        expect(state_data.version).to.equal(1);
       });
 
       it("Check Nft transfered from owner to vault", async () => {
@@ -382,9 +1019,23 @@ describe("rental", async () => {
           rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
           renterAta: renter_fee_ata,
           rentalState: rental_state,
+          referrerStats: null,
+          solVault: null,
+          priceFeed: null,
+          priceUpdate: null,
+          renterProfile: null,
+          banEntry: ban_entry,
+          attestationIssuer: null,
+          attestation: null,
+          allowlist: null,
+          blocklist: null,
+          sysvarInstructions: null,
+          rentalPassMint: rental_pass_mint,
+          renterPass: renter_pass,
           clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         })
         .signers([convert_keypair_to_anchor_compatiable(renter)])
@@ -420,8 +1071,26 @@ describe("rental", async () => {
 
         expect(state_data.rented).to.equal(true);
 
+        expect(state_data.escrowedRent.toString()).to.equal(
+          RENT_FEE.toString()
+        );
+        expect(state_data.escrowedDeposit.toString()).to.equal(
+          DEPOSIT_FEE.toString()
+        );
+
         expect(state_data.rentalStartTime.toString()).to.not.equal(0);
       });
+
+      it("Checking Rental Pass is minted to Renter", async () => {
+        let renter_pass_balance = await getAccount(
+          provider.connection,
+          renter_pass,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        );
+
+        expect(renter_pass_balance.amount.toString()).to.equal("1");
+      });
     });
   });
 
@@ -431,7 +1100,22 @@ describe("rental", async () => {
 
       let owner_ata_balance_before;
 
+      let receipt: anchor.web3.PublicKey;
+
       before(async () => {
+        const rental_state_data = await program.account.rentalState.fetch(
+          rental_state
+        );
+
+        receipt = anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("receipt"),
+            rental_state.toBuffer(),
+            rental_state_data.rentalStartTime.toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        )[0];
+
         renter_ata_info_before = await getAccount(
           provider.connection,
           renter_fee_ata
@@ -446,6 +1130,8 @@ describe("rental", async () => {
           .endRental()
           .accountsStrict({
             owner: new anchor.web3.PublicKey(owner.publicKey),
+            authority: new anchor.web3.PublicKey(owner.publicKey),
+            operatorGrant: null,
             renter: new anchor.web3.PublicKey(renter.publicKey),
             collectionMint: new anchor.web3.PublicKey(
               collection_mint.publicKey
@@ -453,16 +1139,33 @@ describe("rental", async () => {
             carNftMint: new anchor.web3.PublicKey(car_nft_mint.publicKey),
             rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
             rentalState: rental_state,
+            config,
+            treasury: treasury_ata,
+            referrerStats: null,
+            referrerFeeAta: null,
+            referrer: null,
+            solVault: null,
+            ownerProfile: owner_profile,
+            renterProfile: renter_profile,
+            receipt,
+            rentalPassMint: rental_pass_mint,
+            renterPass: renter_pass,
             rentVault: rent_vault_ata,
             vault: vault_ata,
             metadata: new anchor.web3.PublicKey(nftmetadata[0]),
             masterEdition: masterEditionPda[0],
+            ownerTokenRecord: null,
+            vaultTokenRecord: null,
+            authorizationRules: null,
+            authorizationRulesProgram: null,
+            sysvarInstructions: null,
             renterAta: renter_fee_ata,
             ownerAta: owner_ata,
             ownerFeeAta: owner_fee_ata,
             systemProgram: SYSTEM_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenProgram: TOKEN_PROGRAM_ID,
+            token2022Program: TOKEN_2022_PROGRAM_ID,
             metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
             clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
           })
@@ -517,6 +1220,56 @@ describe("rental", async () => {
 
           expect(vault_ata_balance.amount.toString()).to.equal("0");
         });
+
+        it("Checking both profiles record the rental", async () => {
+          const owner_profile_data = await program.account.userProfile.fetch(
+            owner_profile
+          );
+          const renter_profile_data = await program.account.userProfile.fetch(
+            renter_profile
+          );
+
+          expect(owner_profile_data.completedRentals.toString()).to.equal("1");
+          expect(renter_profile_data.totalVolume.toString()).to.equal(
+            RENT_FEE.toString()
+          );
+        });
+
+        it("Checking renter can rate the owner once", async () => {
+          await program.methods
+            .rateRental(5, null)
+            .accountsStrict({
+              rater: new anchor.web3.PublicKey(renter.publicKey),
+              owner: new anchor.web3.PublicKey(owner.publicKey),
+              receipt,
+              rateeProfile: owner_profile,
+            })
+            .signers([convert_keypair_to_anchor_compatiable(renter)])
+            .rpc();
+
+          const owner_profile_data = await program.account.userProfile.fetch(
+            owner_profile
+          );
+          expect(owner_profile_data.ratingCount.toString()).to.equal("1");
+          expect(owner_profile_data.ratingTotal.toString()).to.equal("5");
+
+          let failed = false;
+          try {
+            await program.methods
+              .rateRental(5, null)
+              .accountsStrict({
+                rater: new anchor.web3.PublicKey(renter.publicKey),
+                owner: new anchor.web3.PublicKey(owner.publicKey),
+                receipt,
+                rateeProfile: owner_profile,
+              })
+              .signers([convert_keypair_to_anchor_compatiable(renter)])
+              .rpc();
+          } catch (error) {
+            failed = true;
+          }
+          expect(failed).to.equal(true);
+        });
       });
     } catch (error) {
       console.log(error.logs);
@@ -554,31 +1307,44 @@ describe("rental", async () => {
           .emergencyExit(owner_payout, renter_payout)
           .accountsStrict({
             arbitrator: new anchor.web3.PublicKey(arbitrator.publicKey),
+            config,
             owner: new anchor.web3.PublicKey(owner.publicKey),
+            authority: new anchor.web3.PublicKey(owner.publicKey),
+            operatorGrant: null,
             renter: new anchor.web3.PublicKey(renter.publicKey),
             collectionMint: new anchor.web3.PublicKey(
               collection_mint.publicKey
             ),
+            fleet: null,
             carNftMint: new anchor.web3.PublicKey(car_nft_mint.publicKey),
             rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
             rentalState: rental_state,
             rentVault: rent_vault_ata,
+            solVault: null,
+            ownerProfile: owner_profile,
+            renterProfile: renter_profile,
+            referrerStats: null,
+            banEntry: ban_entry,
+            rentalPassMint: rental_pass_mint,
+            renterPass: renter_pass,
             vault: vault_ata,
             metadata: new anchor.web3.PublicKey(nftmetadata[0]),
             masterEdition: masterEditionPda[0],
+            ownerTokenRecord: null,
+            vaultTokenRecord: null,
+            authorizationRules: null,
+            authorizationRulesProgram: null,
+            sysvarInstructions: null,
             renterAta: renter_fee_ata,
             ownerAta: owner_ata,
             ownerFeeAta: owner_fee_ata,
             systemProgram: SYSTEM_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             tokenProgram: TOKEN_PROGRAM_ID,
+            token2022Program: TOKEN_2022_PROGRAM_ID,
             metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
           })
-          .signers([
-            convert_keypair_to_anchor_compatiable(owner),
-            convert_keypair_to_anchor_compatiable(renter),
-            arbitrator,
-          ])
+          .signers([convert_keypair_to_anchor_compatiable(owner), arbitrator])
           .rpc();
 
         // console.log("emergency exit tx", tx);
@@ -648,4 +1414,2050 @@ describe("rental", async () => {
       });
     });
   });
+
+  describe("Ban on a lost dispute", async () => {
+    let tenant: any;
+
+    before(async () => {
+      tenant = await new_renter();
+      const car = await list_new_car();
+      await rent_listed_car(car, tenant);
+
+      //funding the ban address up front must not keep the ruling from landing
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: tenant.ban_entry,
+            lamports: 1_000_000,
+          })
+        )
+      );
+
+      await exit_rental(car, tenant, new anchor.BN(0), RENT_FEE.add(DEPOSIT_FEE));
+    });
+
+    it("Checking a pre-funded ban address still records the ban", async () => {
+      const ban = await program.account.banEntry.fetch(tenant.ban_entry);
+      expect(ban.wallet.toString()).to.equal(tenant.key.toString());
+      expect(ban.reason).to.have.property("lostDispute");
+    });
+
+    it("Checking a banned renter cannot rent", async () => {
+      const car = await list_new_car();
+      await airdrop_rent_token(
+        tenant.fee_ata,
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber(),
+        rent_fee_mint
+      );
+      await expect_error(rent_listed_car(car, tenant), "RenterBanned");
+    });
+
+    it("Checking a ruling cannot be applied to a wallet that is not the renter", async () => {
+      const car = await list_new_car();
+      const renter = await new_renter();
+      const bystander = await new_renter();
+      await rent_listed_car(car, renter);
+
+      await expect_error(
+        exit_rental(car, bystander, new anchor.BN(0), RENT_FEE.add(DEPOSIT_FEE)),
+        "InvalidRenter"
+      );
+    });
+  });
+
+  describe("Fractional ownership", async () => {
+    const TOTAL_SHARES = 100;
+    const STAKED_SHARES = 60;
+    let car: any;
+    let fraction_pool: anchor.web3.PublicKey;
+    let share_mint: anchor.web3.PublicKey;
+    let reward_vault: anchor.web3.PublicKey;
+    let owner_shares: anchor.web3.PublicKey;
+    let owner_rewards: anchor.web3.PublicKey;
+    let position: anchor.web3.PublicKey;
+    const owner_key = () => new anchor.web3.PublicKey(owner.publicKey);
+
+    const share_accounts = () => ({
+      holder: owner_key(),
+      fractionPool: fraction_pool,
+      position,
+      shareMint: share_mint,
+      holderShares: owner_shares,
+      shareVault: getAssociatedTokenAddressSync(share_mint, fraction_pool, true),
+      rewardMint: new anchor.web3.PublicKey(rent_fee_mint),
+      rewardVault: reward_vault,
+      holderRewards: owner_rewards,
+      systemProgram: SYSTEM_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      shareTokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    const redeem = () =>
+      program.methods
+        .redeemCar()
+        .accountsStrict({
+          holder: owner_key(),
+          owner: owner_key(),
+          carNftMint: car.mint,
+          rentalState: car.rental_state,
+          fractionPool: fraction_pool,
+          shareMint: share_mint,
+          holderShares: owner_shares,
+          rewardMint: new anchor.web3.PublicKey(rent_fee_mint),
+          rewardVault: reward_vault,
+          holderRewards: owner_rewards,
+          vault: car.vault,
+          holderNftAccount: car.owner_ata,
+          metadata: car.metadata,
+          masterEdition: car.master_edition,
+          holderTokenRecord: null,
+          vaultTokenRecord: null,
+          authorizationRules: null,
+          authorizationRulesProgram: null,
+          sysvarInstructions: null,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          shareTokenProgram: TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+    before(async () => {
+      car = await list_new_car();
+
+      fraction_pool = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("fraction"), car.rental_state.toBuffer()],
+        program.programId
+      )[0];
+      share_mint = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("fraction_shares"), car.rental_state.toBuffer()],
+        program.programId
+      )[0];
+      reward_vault = getAssociatedTokenAddressSync(
+        new anchor.web3.PublicKey(rent_fee_mint),
+        fraction_pool,
+        true
+      );
+      owner_shares = getAssociatedTokenAddressSync(share_mint, owner_key());
+      owner_rewards = owner_fee_ata;
+      position = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("share_position"),
+          fraction_pool.toBuffer(),
+          owner_key().toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      await program.methods
+        .fractionalizeCar(new anchor.BN(TOTAL_SHARES))
+        .accountsStrict({
+          owner: owner_key(),
+          carNftMint: car.mint,
+          rentalState: car.rental_state,
+          rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
+          fractionPool: fraction_pool,
+          shareMint: share_mint,
+          rewardVault: reward_vault,
+          ownerShares: owner_shares,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          shareTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      await program.methods
+        .stakeShares(new anchor.BN(STAKED_SHARES))
+        .accountsStrict(share_accounts())
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      const tenant = await new_renter();
+      await rent_listed_car(car, tenant);
+      await return_car(car, tenant, {}, [reward_vault]);
+    });
+
+    it("Checking the rent lands in the reward vault and the car stays locked", async () => {
+      const reward_vault_info = await getAccount(connection, reward_vault);
+      const vault_info = await getAccount(connection, car.vault);
+      const state = await program.account.rentalState.fetch(car.rental_state);
+
+      expect(reward_vault_info.amount.toString()).to.equal(RENT_FEE.toString());
+      expect(vault_info.amount.toString()).to.equal("1");
+      expect(state.status).to.have.property("active");
+    });
+
+    it("Checking staked shares claim their part of the rent and the unstaked part stays in the vault", async () => {
+      const before = await getAccount(connection, owner_rewards);
+
+      await program.methods
+        .claimShareRewards()
+        .accountsStrict({
+          holder: owner_key(),
+          fractionPool: fraction_pool,
+          position,
+          rewardMint: new anchor.web3.PublicKey(rent_fee_mint),
+          rewardVault: reward_vault,
+          holderRewards: owner_rewards,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      const after = await getAccount(connection, owner_rewards);
+      const reward_vault_info = await getAccount(connection, reward_vault);
+
+      const staked_part = Math.floor(
+        (RENT_FEE.toNumber() * STAKED_SHARES) / TOTAL_SHARES
+      );
+      expect(Number(after.amount) - Number(before.amount)).to.equal(staked_part);
+      expect(Number(reward_vault_info.amount)).to.equal(
+        RENT_FEE.toNumber() - staked_part
+      );
+    });
+
+    it("Checking a holder cannot unstake more than they staked", async () => {
+      await expect_error(
+        program.methods
+          .unstakeShares(new anchor.BN(STAKED_SHARES + 1))
+          .accountsStrict(share_accounts())
+          .signers([convert_keypair_to_anchor_compatiable(owner)])
+          .rpc(),
+        "InsufficientShares"
+      );
+    });
+
+    it("Checking the car cannot be redeemed while shares are staked", async () => {
+      await expect_error(redeem(), "InsufficientShares");
+    });
+
+    it("Checking burning every share redeems the car", async () => {
+      await program.methods
+        .unstakeShares(new anchor.BN(STAKED_SHARES))
+        .accountsStrict(share_accounts())
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      await redeem();
+
+      const owner_nft = await getAccount(connection, car.owner_ata);
+      const shares = await getAccount(connection, owner_shares);
+
+      expect(owner_nft.amount.toString()).to.equal("1");
+      expect(shares.amount.toString()).to.equal("0");
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+      expect(await connection.getAccountInfo(fraction_pool)).to.equal(null);
+    });
+  });
+
+  describe("Fractional ownership with staggered stakers", async () => {
+    const TOTAL_SHARES = 100;
+    const HALF = TOTAL_SHARES / 2;
+    let car: any;
+    let fraction_pool: anchor.web3.PublicKey;
+    let share_mint: anchor.web3.PublicKey;
+    let reward_vault: anchor.web3.PublicKey;
+    let late: any;
+    const owner_key = () => new anchor.web3.PublicKey(owner.publicKey);
+    const reward_mint = () => new anchor.web3.PublicKey(rent_fee_mint);
+
+    const holder_accounts = (holder: anchor.web3.PublicKey) => ({
+      holder,
+      fractionPool: fraction_pool,
+      position: anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("share_position"),
+          fraction_pool.toBuffer(),
+          holder.toBuffer(),
+        ],
+        program.programId
+      )[0],
+      rewardMint: reward_mint(),
+      rewardVault: reward_vault,
+      holderRewards: getAssociatedTokenAddressSync(reward_mint(), holder),
+      systemProgram: SYSTEM_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    const stake = (holder: anchor.web3.PublicKey, signer: Keypair) =>
+      program.methods
+        .stakeShares(new anchor.BN(HALF))
+        .accountsStrict({
+          ...holder_accounts(holder),
+          shareMint: share_mint,
+          holderShares: getAssociatedTokenAddressSync(share_mint, holder),
+          shareVault: getAssociatedTokenAddressSync(
+            share_mint,
+            fraction_pool,
+            true
+          ),
+          shareTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+
+    const claim = async (holder: anchor.web3.PublicKey, signer: Keypair) => {
+      const rewards = getAssociatedTokenAddressSync(reward_mint(), holder);
+      const before = Number((await getAccount(connection, rewards)).amount);
+      await program.methods
+        .claimShareRewards()
+        .accountsStrict(holder_accounts(holder))
+        .signers([signer])
+        .rpc();
+      return Number((await getAccount(connection, rewards)).amount) - before;
+    };
+
+    const rent_and_return = async () => {
+      const tenant = await new_renter();
+      await rent_listed_car(car, tenant);
+      await return_car(car, tenant, {}, [reward_vault]);
+    };
+
+    before(async () => {
+      car = await list_new_car();
+      late = await new_renter();
+
+      fraction_pool = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("fraction"), car.rental_state.toBuffer()],
+        program.programId
+      )[0];
+      share_mint = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("fraction_shares"), car.rental_state.toBuffer()],
+        program.programId
+      )[0];
+      reward_vault = getAssociatedTokenAddressSync(
+        reward_mint(),
+        fraction_pool,
+        true
+      );
+      const owner_shares = getAssociatedTokenAddressSync(share_mint, owner_key());
+
+      await program.methods
+        .fractionalizeCar(new anchor.BN(TOTAL_SHARES))
+        .accountsStrict({
+          owner: owner_key(),
+          carNftMint: car.mint,
+          rentalState: car.rental_state,
+          rentFeeMint: reward_mint(),
+          fractionPool: fraction_pool,
+          shareMint: share_mint,
+          rewardVault: reward_vault,
+          ownerShares: owner_shares,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          shareTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      // half the shares go to a second holder who stakes later
+      const late_shares = await token_account(share_mint, late.key);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createTransferInstruction(owner_shares, late_shares, owner_key(), HALF)
+        ),
+        [convert_keypair_to_anchor_compatiable(owner)]
+      );
+
+      // rent from before anyone staked, then the owner stakes, then more rent, then the second holder stakes
+      await rent_and_return();
+      await stake(owner_key(), convert_keypair_to_anchor_compatiable(owner));
+      await rent_and_return();
+      await stake(late.key, late.keypair);
+    });
+
+    it("Checking each holder only earns the rent that arrived while their shares were staked", async () => {
+      const owner_part = Math.floor((RENT_FEE.toNumber() * HALF) / TOTAL_SHARES);
+
+      expect(
+        await claim(owner_key(), convert_keypair_to_anchor_compatiable(owner))
+      ).to.equal(owner_part);
+      expect(await claim(late.key, late.keypair)).to.equal(0);
+
+      const left = await getAccount(connection, reward_vault);
+      expect(Number(left.amount)).to.equal(2 * RENT_FEE.toNumber() - owner_part);
+    });
+  });
+
+  describe("Mileage billing", async () => {
+    const INCLUDED_KM = 10;
+    const FEE_PER_KM = 1;
+    const START_KM = 100;
+    const DRIVEN_KM = 13;
+    const device = anchor.web3.Keypair.generate();
+    let car: any;
+    let tenant: any;
+
+    // the car's device signing its odometer for this listing, as the instruction right before the program's
+    const reading = async (km: number) => {
+      const now = await connection.getBlockTime(await connection.getSlot());
+      const body = Buffer.concat([
+        new anchor.BN(km).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(now).toArrayLike(Buffer, "le", 8),
+      ]);
+
+      return anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: device.secretKey,
+        message: Buffer.concat([
+          Buffer.from("odometer"),
+          car.rental_state.toBuffer(),
+          body,
+        ]),
+      });
+    };
+
+    before(async () => {
+      car = await list_new_car();
+      tenant = await new_renter();
+
+      await program.methods
+        .setMileagePricing({
+          device: device.publicKey,
+          includedKm: new anchor.BN(INCLUDED_KM),
+          feePerKm: new anchor.BN(FEE_PER_KM),
+        })
+        .accountsStrict({
+          authority: new anchor.web3.PublicKey(owner.publicKey),
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          operatorGrant: null,
+          carNftMint: car.mint,
+          rentalState: car.rental_state,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      await rent_listed_car(
+        car,
+        tenant,
+        { sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY },
+        [await reading(START_KM)]
+      );
+    });
+
+    it("Checking a return without an odometer reading is rejected", async () => {
+      await expect_error(return_car(car, tenant), "MissingOdometerReading");
+    });
+
+    it("Checking km past the allowance are charged from the deposit", async () => {
+      const renter_before = await getAccount(connection, tenant.fee_ata);
+      const owner_before = await getAccount(connection, owner_fee_ata);
+      const overage = (DRIVEN_KM - INCLUDED_KM) * FEE_PER_KM;
+
+      await return_car(
+        car,
+        tenant,
+        { sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY },
+        [],
+        [await reading(START_KM + DRIVEN_KM)]
+      );
+
+      const renter_after = await getAccount(connection, tenant.fee_ata);
+      const owner_after = await getAccount(connection, owner_fee_ata);
+
+      expect(
+        (Number(renter_after.amount) - Number(renter_before.amount)).toString()
+      ).to.equal((DEPOSIT_FEE.toNumber() - overage).toString());
+      expect(
+        (Number(owner_after.amount) - Number(owner_before.amount)).toString()
+      ).to.equal((RENT_FEE.toNumber() + overage).toString());
+    });
+  });
+
+  describe("Transfer fee mints", async () => {
+    const FEE_BPS = 100;
+    let fee_mint: anchor.web3.PublicKey;
+    let car: any;
+    let tenant: any;
+
+    before(async () => {
+      fee_mint = await create_token_2022_mint(
+        ExtensionType.TransferFeeConfig,
+        (mint) =>
+          createInitializeTransferFeeConfigInstruction(
+            mint,
+            provider.wallet.publicKey,
+            provider.wallet.publicKey,
+            FEE_BPS,
+            BigInt(1_000_000),
+            TOKEN_2022_PROGRAM_ID
+          )
+      );
+
+      car = await list(await new_car(fee_mint, TOKEN_2022_PROGRAM_ID), {
+        transferFeePayer: { renter: {} },
+      });
+      tenant = await new_renter(fee_mint, TOKEN_2022_PROGRAM_ID);
+    });
+
+    it("Checking a renter without the fee on top is rejected", async () => {
+      const short = await new_renter(
+        fee_mint,
+        TOKEN_2022_PROGRAM_ID,
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber()
+      );
+      await expect_error(rent_listed_car(car, short), "InsufficientFunds");
+    });
+
+    it("Checking the quoted rent and deposit arrive in full when the renter pays fees", async () => {
+      await rent_listed_car(car, tenant);
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.escrowedRent.toString()).to.equal(RENT_FEE.toString());
+      expect(state.escrowedDeposit.toString()).to.equal(DEPOSIT_FEE.toString());
+      expect(await balance(car.rent_vault, TOKEN_2022_PROGRAM_ID)).to.equal(
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber()
+      );
+    });
+
+    it("Checking the owner receives the full rent at settlement", async () => {
+      const owner_before = await balance(car.owner_fee_ata, TOKEN_2022_PROGRAM_ID);
+
+      await return_car(car, tenant);
+
+      expect(
+        (await balance(car.owner_fee_ata, TOKEN_2022_PROGRAM_ID)) - owner_before
+      ).to.equal(RENT_FEE.toNumber());
+    });
+  });
+
+  describe("Transfer hook mints", async () => {
+    let hook_mint: anchor.web3.PublicKey;
+
+    before(async () => {
+      //the hook program starts unset, so transfers go through with no extra accounts
+      hook_mint = await create_token_2022_mint(ExtensionType.TransferHook, (mint) =>
+        createInitializeTransferHookInstruction(
+          mint,
+          provider.wallet.publicKey,
+          anchor.web3.PublicKey.default,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+    });
+
+    it("Checking a hook mint pays rent in and out", async () => {
+      const car = await list(await new_car(hook_mint, TOKEN_2022_PROGRAM_ID));
+      const tenant = await new_renter(hook_mint, TOKEN_2022_PROGRAM_ID);
+
+      await rent_listed_car(car, tenant);
+      const owner_before = await balance(car.owner_fee_ata, TOKEN_2022_PROGRAM_ID);
+      await return_car(car, tenant);
+
+      expect(
+        (await balance(car.owner_fee_ata, TOKEN_2022_PROGRAM_ID)) - owner_before
+      ).to.equal(RENT_FEE.toNumber());
+    });
+
+    it("Checking a payment without the hook's accounts is rejected", async () => {
+      const car = await list(await new_car(hook_mint, TOKEN_2022_PROGRAM_ID));
+      const tenant = await new_renter(hook_mint, TOKEN_2022_PROGRAM_ID);
+
+      //any program works as the hook here, the renter passes none of its extra accounts
+      await updateTransferHook(
+        connection,
+        provider.wallet.payer,
+        hook_mint,
+        program.programId,
+        provider.wallet.publicKey,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      let failed = false;
+      try {
+        await rent_listed_car(car, tenant);
+      } catch (error) {
+        failed = true;
+      }
+      expect(failed).to.equal(true);
+    });
+  });
+
+  describe("Lamport listings", async () => {
+    let car: any;
+    let tenant: any;
+    let sol_vault: anchor.web3.PublicKey;
+
+    const lamport_accounts = () => ({
+      rentFeeMint: null,
+      rentVault: null,
+      renterAta: null,
+      solVault: sol_vault,
+    });
+
+    before(async () => {
+      car = await new_car();
+      sol_vault = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("sol_vault"), car.rental_state.toBuffer()],
+        program.programId
+      )[0];
+
+      await list(
+        car,
+        { paymentKind: { lamports: {} } },
+        { rentFeeMint: null, ownerFeeAta: null, solVault: sol_vault }
+      );
+      tenant = await new_renter();
+    });
+
+    it("Checking a lamport rental without the sol vault is rejected", async () => {
+      await expect_error(
+        rent_listed_car(car, tenant, { ...lamport_accounts(), solVault: null }),
+        "MissingPaymentAccounts"
+      );
+    });
+
+    it("Checking rent and deposit are escrowed in the sol vault", async () => {
+      const reserve = await connection.getBalance(sol_vault);
+
+      await rent_listed_car(car, tenant, lamport_accounts());
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.escrowedRent.toString()).to.equal(RENT_FEE.toString());
+      expect(state.escrowedDeposit.toString()).to.equal(DEPOSIT_FEE.toString());
+      expect(await connection.getBalance(sol_vault)).to.equal(
+        reserve + RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber()
+      );
+    });
+
+    it("Checking the return empties the sol vault and closes the listing", async () => {
+      await return_car(car, tenant, {
+        ...lamport_accounts(),
+        treasury: null,
+        ownerFeeAta: null,
+      });
+
+      expect(await connection.getBalance(sol_vault)).to.equal(0);
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+      expect(await balance(car.owner_ata)).to.equal(1);
+    });
+  });
+
+  describe("Programmable NFT cars", async () => {
+    let car: any;
+
+    // token records of the owner's account and the vault, token-metadata moves pnfts between them
+    const pnft_accounts = () => ({
+      ownerTokenRecord: new anchor.web3.PublicKey(
+        findTokenRecordPda(umi, {
+          mint: publicKey(car.mint.toBase58()),
+          token: publicKey(car.owner_ata.toBase58()),
+        })[0]
+      ),
+      vaultTokenRecord: new anchor.web3.PublicKey(
+        findTokenRecordPda(umi, {
+          mint: publicKey(car.mint.toBase58()),
+          token: publicKey(car.vault.toBase58()),
+        })[0]
+      ),
+      sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    });
+
+    before(async () => {
+      car = await new_car(
+        new anchor.web3.PublicKey(rent_fee_mint),
+        TOKEN_PROGRAM_ID,
+        true
+      );
+    });
+
+    it("Checking a pnft listing without token records is rejected", async () => {
+      await expect_error(list(car), "MissingPnftAccounts");
+    });
+
+    it("Checking a pnft is vaulted, rented and handed back", async () => {
+      await list(car, {}, pnft_accounts());
+      expect(await balance(car.vault)).to.equal(1);
+
+      const tenant = await new_renter();
+      await rent_listed_car(car, tenant);
+      await return_car(car, tenant, pnft_accounts());
+
+      expect(await balance(car.owner_ata)).to.equal(1);
+      expect(await balance(car.vault)).to.equal(0);
+    });
+  });
+
+  describe("Core asset cars", async () => {
+    let car: any;
+    let collection: anchor.web3.PublicKey;
+    let tenant: any;
+
+    const list_core = (options: any = {}) =>
+      program.methods
+        .listCoreCar(RENT_FEE, DEPOSIT_FEE, { ...default_options, ...options })
+        .accountsStrict({
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          asset: car.mint,
+          collection,
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          ownerFeeAta: car.owner_fee_ata,
+          solVault: null,
+          coreProgram: new anchor.web3.PublicKey(MPL_CORE_PROGRAM_ID),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+    before(async () => {
+      umi.use(mplCore());
+
+      const core_collection = generateSigner(umi);
+      await createCoreCollection(umi, {
+        collection: core_collection,
+        name: "rental_core_collection",
+        uri: "https://arweave.net/123",
+      }).sendAndConfirm(umi);
+
+      const asset = generateSigner(umi);
+      await createCoreAsset(umi, {
+        asset,
+        collection: await fetchCollection(umi, core_collection.publicKey),
+        owner: owner.publicKey,
+        name: "Konessige",
+        uri: "https://arweave.net/123",
+      }).sendAndConfirm(umi);
+
+      collection = new anchor.web3.PublicKey(core_collection.publicKey);
+      const asset_key = new anchor.web3.PublicKey(asset.publicKey);
+      const state = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("rental"),
+          asset_key.toBuffer(),
+          new anchor.web3.PublicKey(owner.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+      const pay_mint = new anchor.web3.PublicKey(rent_fee_mint);
+
+      car = {
+        mint: asset_key,
+        pay_mint,
+        token_program: TOKEN_PROGRAM_ID,
+        rental_state: state,
+        rental_pass_mint: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("rental_pass"), state.toBuffer()],
+          program.programId
+        )[0],
+        rent_vault: await token_account(pay_mint, state),
+        owner_fee_ata,
+        treasury: treasury_ata,
+      };
+      tenant = await new_renter();
+    });
+
+    it("Checking a frozen custody core listing is rejected", async () => {
+      await expect_error(
+        list_core({ custody: { frozen: {} } }),
+        "UnsupportedCustody"
+      );
+    });
+
+    it("Checking a core asset is held by the listing, rented and handed back", async () => {
+      await list_core();
+      expect(
+        (await fetchAsset(umi, publicKey(car.mint.toBase58()))).owner.toString()
+      ).to.equal(car.rental_state.toBase58());
+
+      await rent_listed_car(car, tenant);
+
+      await program.methods
+        .endCoreRental()
+        .accountsStrict({
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          authority: new anchor.web3.PublicKey(owner.publicKey),
+          operatorGrant: null,
+          renter: tenant.key,
+          asset: car.mint,
+          collection,
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          config,
+          treasury: car.treasury,
+          referrerStats: null,
+          referrerFeeAta: null,
+          referrer: null,
+          rentVault: car.rent_vault,
+          solVault: null,
+          renterAta: tenant.fee_ata,
+          ownerFeeAta: car.owner_fee_ata,
+          coreProgram: new anchor.web3.PublicKey(MPL_CORE_PROGRAM_ID),
+          ownerProfile: owner_profile,
+          renterProfile: tenant.profile,
+          receipt: await receipt_of(car.rental_state),
+          rentalPassMint: car.rental_pass_mint,
+          renterPass: getAssociatedTokenAddressSync(
+            car.rental_pass_mint,
+            tenant.key,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([tenant.keypair, convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      expect(
+        (await fetchAsset(umi, publicKey(car.mint.toBase58()))).owner.toString()
+      ).to.equal(owner.publicKey.toString());
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+    });
+  });
+
+  describe("Compressed cars", async () => {
+    const MAX_DEPTH = 3;
+    let car: any;
+    let tenant: any;
+    let merkle_tree: anchor.web3.PublicKey;
+    let compressed_accounts: any;
+    let metadata: any;
+
+    // the car's leaf as the program rebuilds it, against the tree's current root.
+    // the canopy holds every level but the bottom one, whose proof node is leaf 0's empty sibling
+    const leaf = async (nonce = 0) => {
+      const tree = await fetchMerkleTree(umi, publicKey(merkle_tree.toBase58()));
+      return {
+        assetId: car.mint,
+        root: Array.from(getCurrentRoot(tree.tree)),
+        nonce: new anchor.BN(nonce),
+        index: 0,
+        proofLen: 1,
+        metadata: {
+          name: metadata.name,
+          symbol: metadata.symbol,
+          uri: metadata.uri,
+          sellerFeeBasisPoints: metadata.sellerFeeBasisPoints,
+          primarySaleHappened: metadata.primarySaleHappened,
+          isMutable: metadata.isMutable,
+          editionNonce: null,
+          tokenStandard: { nonFungible: {} },
+          collection: {
+            verified: true,
+            key: new anchor.web3.PublicKey(collection_mint.publicKey),
+          },
+          uses: null,
+          tokenProgramVersion: { original: {} },
+          creators: [],
+        },
+      };
+    };
+    const proof = [
+      {
+        pubkey: anchor.web3.PublicKey.default,
+        isSigner: false,
+        isWritable: false,
+      },
+    ];
+
+    const list_compressed = async (nonce = 0) =>
+      program.methods
+        .listCompressedCar(
+          RENT_FEE,
+          DEPOSIT_FEE,
+          default_options,
+          await leaf(nonce)
+        )
+        .accountsStrict({
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          ownerFeeAta: car.owner_fee_ata,
+          solVault: null,
+          ...compressed_accounts,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(proof)
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+    before(async () => {
+      umi.use(mplBubblegum());
+
+      const tree = generateSigner(umi);
+      await (
+        await createTree(umi, {
+          merkleTree: tree,
+          maxDepth: MAX_DEPTH,
+          maxBufferSize: 8,
+          canopyDepth: MAX_DEPTH - 1,
+        })
+      ).sendAndConfirm(umi);
+      merkle_tree = new anchor.web3.PublicKey(tree.publicKey);
+
+      metadata = {
+        name: "Konessige",
+        symbol: "KO",
+        uri: "https://arweave.net/123",
+        sellerFeeBasisPoints: 0,
+        primarySaleHappened: false,
+        isMutable: true,
+        editionNonce: null,
+        tokenStandard: TokenStandard.NonFungible,
+        collection: { key: collection_mint.publicKey, verified: false },
+        uses: null,
+        tokenProgramVersion: TokenProgramVersion.Original,
+        creators: [],
+      };
+      await mintToCollectionV1(umi, {
+        leafOwner: owner.publicKey,
+        merkleTree: tree.publicKey,
+        collectionMint: collection_mint.publicKey,
+        metadata,
+      }).sendAndConfirm(umi);
+
+      const asset_id = new anchor.web3.PublicKey(
+        findLeafAssetIdPda(umi, { merkleTree: tree.publicKey, leafIndex: 0 })[0]
+      );
+      const state = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("rental"),
+          asset_id.toBuffer(),
+          new anchor.web3.PublicKey(owner.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+      const pay_mint = new anchor.web3.PublicKey(rent_fee_mint);
+
+      car = {
+        mint: asset_id,
+        pay_mint,
+        token_program: TOKEN_PROGRAM_ID,
+        rental_state: state,
+        rental_pass_mint: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("rental_pass"), state.toBuffer()],
+          program.programId
+        )[0],
+        rent_vault: await token_account(pay_mint, state),
+        owner_fee_ata,
+        treasury: treasury_ata,
+      };
+      compressed_accounts = {
+        treeConfig: new anchor.web3.PublicKey(
+          findTreeConfigPda(umi, { merkleTree: tree.publicKey })[0]
+        ),
+        merkleTree: merkle_tree,
+        logWrapper: new anchor.web3.PublicKey(SPL_NOOP_PROGRAM_ID),
+        compressionProgram: new anchor.web3.PublicKey(
+          SPL_ACCOUNT_COMPRESSION_PROGRAM_ID
+        ),
+        bubblegumProgram: new anchor.web3.PublicKey(MPL_BUBBLEGUM_PROGRAM_ID),
+      };
+      tenant = await new_renter();
+    });
+
+    it("Checking a leaf that is not the listed asset is rejected", async () => {
+      await expect_error(list_compressed(1), "InvalidCompressedLeaf");
+    });
+
+    it("Checking a compressed car is held by the listing, rented and handed back", async () => {
+      await list_compressed();
+      await rent_listed_car(car, tenant);
+
+      await program.methods
+        .endCompressedRental(await leaf())
+        .accountsStrict({
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          authority: new anchor.web3.PublicKey(owner.publicKey),
+          operatorGrant: null,
+          renter: tenant.key,
+          collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          config,
+          treasury: car.treasury,
+          referrerStats: null,
+          referrerFeeAta: null,
+          referrer: null,
+          rentVault: car.rent_vault,
+          solVault: null,
+          renterAta: tenant.fee_ata,
+          ownerFeeAta: car.owner_fee_ata,
+          ...compressed_accounts,
+          ownerProfile: owner_profile,
+          renterProfile: tenant.profile,
+          receipt: await receipt_of(car.rental_state),
+          rentalPassMint: car.rental_pass_mint,
+          renterPass: getAssociatedTokenAddressSync(
+            car.rental_pass_mint,
+            tenant.key,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(proof)
+        .signers([tenant.keypair, convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      //the only leaf in the tree is the car, owned and delegated by the owner again
+      const owner_leaf = hashLeaf(umi, {
+        merkleTree: publicKey(merkle_tree.toBase58()),
+        owner: owner.publicKey,
+        leafIndex: 0,
+        metadata: {
+          ...metadata,
+          collection: { key: collection_mint.publicKey, verified: true },
+        },
+      });
+      const tree = await fetchMerkleTree(umi, publicKey(merkle_tree.toBase58()));
+
+      expect(Buffer.from(getCurrentRoot(tree.tree)).toString("hex")).to.equal(
+        Buffer.from(
+          getMerkleRoot([publicKey(owner_leaf)], MAX_DEPTH)
+        ).toString("hex")
+      );
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+    });
+  });
+
+  describe("Frozen custody", async () => {
+    let car: any;
+    let tenant: any;
+
+    const frozen = { custody: { frozen: {} } };
+
+    before(async () => {
+      car = await list(await new_car(), frozen, { vault: null });
+      tenant = await new_renter();
+    });
+
+    it("Checking a frozen car stays in the owner's wallet, delegated to the listing", async () => {
+      const account = await getAccount(provider.connection, car.owner_ata);
+      expect(Number(account.amount)).to.equal(1);
+      expect(account.isFrozen).to.equal(true);
+      expect(account.delegate.toString()).to.equal(car.rental_state.toString());
+    });
+
+    it("Checking the owner cannot move a frozen car while it is listed", async () => {
+      const owner_key = new anchor.web3.PublicKey(owner.publicKey);
+      const other_ata = await token_account(car.mint, provider.wallet.publicKey);
+
+      let failed = false;
+      try {
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            createTransferInstruction(car.owner_ata, other_ata, owner_key, 1)
+          ),
+          [convert_keypair_to_anchor_compatiable(owner)]
+        );
+      } catch (error) {
+        failed = true;
+      }
+      expect(failed).to.equal(true);
+      expect(await balance(car.owner_ata)).to.equal(1);
+    });
+
+    it("Checking returning a frozen car thaws it and revokes the listing", async () => {
+      await rent_listed_car(car, tenant);
+      await return_car(car, tenant, { vault: null });
+
+      const account = await getAccount(provider.connection, car.owner_ata);
+      expect(Number(account.amount)).to.equal(1);
+      expect(account.isFrozen).to.equal(false);
+      expect(account.delegate).to.equal(null);
+    });
+  });
+
+  describe("Fleet rentals", async () => {
+    let car: any;
+    let fleet: anchor.web3.PublicKey;
+    let fleet_vault: anchor.web3.PublicKey;
+    let tenant: any;
+
+    const owner_key = () => new anchor.web3.PublicKey(owner.publicKey);
+
+    // the unit's rental_state is created by the renter, the rest mirrors rent_car
+    const rent_unit = (unit: any, tenant: any) =>
+      program.methods
+        .rentFleetCar(new anchor.BN(RENTAL_DURATION))
+        .accountsStrict({
+          renter: tenant.key,
+          owner: owner_key(),
+          fleet,
+          carNftMint: unit.mint,
+          rentFeeMint: unit.pay_mint,
+          rentalState: unit.rental_state,
+          rentVault: unit.rent_vault,
+          solVault: null,
+          referrerStats: null,
+          renterAta: tenant.fee_ata,
+          attestationIssuer: null,
+          attestation: null,
+          banEntry: tenant.ban_entry,
+          allowlist: null,
+          blocklist: null,
+          rentalPassMint: unit.rental_pass_mint,
+          renterPass: getAssociatedTokenAddressSync(
+            unit.rental_pass_mint,
+            tenant.key,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([tenant.keypair])
+        .rpc();
+
+    before(async () => {
+      fleet = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("fleet"),
+          owner_key().toBuffer(),
+          new anchor.web3.PublicKey(collection_mint.publicKey).toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+      await program.methods
+        .createFleet(RENT_FEE, DEPOSIT_FEE, default_options)
+        .accountsStrict({
+          owner: owner_key(),
+          authority: owner_key(),
+          operatorGrant: null,
+          collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+          rentFeeMint: new anchor.web3.PublicKey(rent_fee_mint),
+          fleet,
+          ownerFeeAta: owner_fee_ata,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      car = await new_car();
+      fleet_vault = await token_account(car.mint, fleet);
+      await program.methods
+        .addFleetCar()
+        .accountsStrict({
+          owner: owner_key(),
+          carNftMint: car.mint,
+          fleet,
+          ownerNftAccount: car.owner_ata,
+          fleetVault: fleet_vault,
+          metadata: car.metadata,
+          masterEdition: car.master_edition,
+          ownerTokenRecord: null,
+          fleetVaultTokenRecord: null,
+          authorizationRules: null,
+          authorizationRulesProgram: null,
+          sysvarInstructions: null,
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      tenant = await new_renter();
+    });
+
+    it("Checking a car outside the fleet cannot be rented from it", async () => {
+      await expect_error(
+        rent_unit(await new_car(), await new_renter()),
+        "FleetUnitUnavailable"
+      );
+    });
+
+    it("Checking a fleet unit cannot be paid for in another mint", async () => {
+      const other_mint = await createMint(
+        connection,
+        provider.wallet.payer,
+        provider.wallet.publicKey,
+        null,
+        9
+      );
+
+      await expect_error(
+        rent_unit(
+          {
+            ...car,
+            pay_mint: other_mint,
+            rent_vault: getAssociatedTokenAddressSync(
+              other_mint,
+              car.rental_state,
+              true
+            ),
+          },
+          await new_renter(other_mint)
+        ),
+        "InvalidPaymentMint"
+      );
+    });
+
+    it("Checking a fleet unit is rented out of the pool", async () => {
+      expect(await balance(fleet_vault)).to.equal(1);
+
+      await rent_unit(car, tenant);
+
+      const pool = await program.account.fleetPool.fetch(fleet);
+      expect(pool.available.length).to.equal(0);
+      expect(pool.rentedUnits).to.equal(1);
+      expect(await balance(car.rent_vault)).to.equal(
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber()
+      );
+    });
+
+    it("Checking a returned unit goes back to the pool and the renter gets the state's rent", async () => {
+      const owner_before = await balance(car.owner_fee_ata);
+      const renter_before = await balance(tenant.fee_ata);
+      const receipt = await receipt_of(car.rental_state);
+
+      await program.methods
+        .endFleetRental()
+        .accountsStrict({
+          owner: owner_key(),
+          authority: owner_key(),
+          operatorGrant: null,
+          renter: tenant.key,
+          fleet,
+          carNftMint: car.mint,
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          config,
+          treasury: car.treasury,
+          referrerStats: null,
+          referrerFeeAta: null,
+          referrer: null,
+          rentVault: car.rent_vault,
+          solVault: null,
+          renterAta: tenant.fee_ata,
+          ownerFeeAta: car.owner_fee_ata,
+          ownerProfile: owner_profile,
+          renterProfile: tenant.profile,
+          receipt,
+          rentalPassMint: car.rental_pass_mint,
+          renterPass: getAssociatedTokenAddressSync(
+            car.rental_pass_mint,
+            tenant.key,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([tenant.keypair, convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      const pool = await program.account.fleetPool.fetch(fleet);
+      expect(pool.available.map((unit) => unit.toString())).to.deep.equal([
+        car.mint.toString(),
+      ]);
+      expect(pool.rentedUnits).to.equal(0);
+      expect(await balance(fleet_vault)).to.equal(1);
+      expect(await balance(car.owner_fee_ata)).to.equal(
+        owner_before + RENT_FEE.toNumber()
+      );
+      expect(await balance(tenant.fee_ata)).to.equal(
+        renter_before + DEPOSIT_FEE.toNumber()
+      );
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+    });
+
+    it("Checking a ruling puts an abandoned unit back in the pool without the renter", async () => {
+      const absent = await new_renter();
+      await rent_unit(car, absent);
+
+      await exit_rental(car, absent, new anchor.BN(0), RENT_FEE.add(DEPOSIT_FEE), {
+        fleet,
+        vault: null,
+      });
+
+      const pool = await program.account.fleetPool.fetch(fleet);
+      expect(pool.available.map((unit) => unit.toString())).to.deep.equal([
+        car.mint.toString(),
+      ]);
+      expect(pool.rentedUnits).to.equal(0);
+      expect(await balance(fleet_vault)).to.equal(1);
+      expect(await connection.getAccountInfo(car.rental_state)).to.equal(null);
+    });
+  });
+
+  describe("Reference priced listings", async () => {
+    // mock price account loaded by the local validator, see tests/fixtures/mock_price.json.
+    // one whole token is worth one reference unit, published at 1_700_000_000 with a 0.5% confidence interval
+    const MOCK_PRICE = new anchor.web3.PublicKey(
+      "Ebdt3mNdKKsrcff8ns3x2n7QFVBF5YkZ4SDmqiyuR5yH"
+    );
+    const AGES_AGO = new anchor.BN(10 * 365 * 24 * 60 * 60);
+    let pay_mint: anchor.web3.PublicKey;
+    let price_feed: anchor.web3.PublicKey;
+    let car: any;
+
+    const set_feed = async (max_staleness: anchor.BN, max_confidence_bps: number) => {
+      const proposal = await create_proposal({
+        setPriceFeed: {
+          mint: pay_mint,
+          source: { mock: { account: MOCK_PRICE } },
+          maxStaleness: max_staleness,
+          maxConfidenceBps: max_confidence_bps,
+        },
+      });
+
+      await program.methods
+        .setPriceFeed()
+        .accountsStrict({
+          payer: provider.wallet.publicKey,
+          multisig,
+          proposal,
+          config,
+          mint: pay_mint,
+          priceFeed: price_feed,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .rpc();
+    };
+
+    const rent_at_oracle_price = async () =>
+      rent_listed_car(car, await new_renter(pay_mint), {
+        priceFeed: price_feed,
+        priceUpdate: MOCK_PRICE,
+      });
+
+    before(async function () {
+      if (!(await connection.getAccountInfo(MOCK_PRICE))) {
+        this.skip();
+      }
+
+      pay_mint = await createMint(
+        connection,
+        provider.wallet.payer,
+        provider.wallet.publicKey,
+        null,
+        6
+      );
+      price_feed = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("price_feed"), pay_mint.toBuffer()],
+        program.programId
+      )[0];
+      car = await list(await new_car(pay_mint), {
+        denomination: { reference: {} },
+      });
+    });
+
+    it("Checking a price older than the feed allows is rejected", async () => {
+      await set_feed(new anchor.BN(60), 100);
+      await expect_error(rent_at_oracle_price(), "StalePrice");
+    });
+
+    it("Checking a price with a wider confidence than the feed allows is rejected", async () => {
+      await set_feed(AGES_AGO, 10);
+      await expect_error(rent_at_oracle_price(), "PriceConfidenceTooWide");
+    });
+
+    it("Checking the reference price is converted at the oracle price", async () => {
+      await set_feed(AGES_AGO, 100);
+      await rent_at_oracle_price();
+
+      //a reference micro unit is a base unit of a 6 decimal token at a price of one
+      expect(await balance(car.rent_vault)).to.equal(
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber()
+      );
+    });
+  });
+
+  describe("Alternative payment mints", async () => {
+    const ALT_RENT_FEE = 7;
+    const ALT_DEPOSIT_FEE = 3;
+    let car: any;
+    let alt_mint: anchor.web3.PublicKey;
+
+    // the listing's accounts for paying in `mint` instead of the listed mint
+    const paying_in = async (mint: anchor.web3.PublicKey) => ({
+      ...car,
+      pay_mint: mint,
+      rent_vault: await token_account(mint, car.rental_state),
+      owner_fee_ata: await token_account(
+        mint,
+        new anchor.web3.PublicKey(owner.publicKey)
+      ),
+      treasury: await token_account(mint, config),
+    });
+
+    const new_mint = () =>
+      createMint(
+        connection,
+        provider.wallet.payer,
+        provider.wallet.publicKey,
+        null,
+        9
+      );
+
+    before(async () => {
+      alt_mint = await new_mint();
+      car = await list(await new_car(), {
+        acceptedPayments: [
+          {
+            mint: alt_mint,
+            rentFee: new anchor.BN(ALT_RENT_FEE),
+            depositAmount: new anchor.BN(ALT_DEPOSIT_FEE),
+          },
+        ],
+      });
+    });
+
+    it("Checking a mint the listing does not accept is rejected", async () => {
+      const other_mint = await new_mint();
+      const tenant = await new_renter(other_mint);
+      const paid_in_other = await paying_in(other_mint);
+
+      await expect_error(
+        rent_listed_car(paid_in_other, tenant),
+        "InvalidPaymentMint"
+      );
+    });
+
+    it("Checking a rental paid in the second mint is charged and settled in it", async () => {
+      const tenant = await new_renter(alt_mint);
+      const paid_in_alt = await paying_in(alt_mint);
+      const renter_before = await balance(tenant.fee_ata);
+      const owner_before = await balance(paid_in_alt.owner_fee_ata);
+
+      await rent_listed_car(paid_in_alt, tenant);
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.paymentMint.toString()).to.equal(alt_mint.toString());
+      expect(await balance(paid_in_alt.rent_vault)).to.equal(
+        ALT_RENT_FEE + ALT_DEPOSIT_FEE
+      );
+
+      await return_car(paid_in_alt, tenant);
+
+      expect(await balance(paid_in_alt.owner_fee_ata)).to.equal(
+        owner_before + ALT_RENT_FEE
+      );
+      expect(await balance(tenant.fee_ata)).to.equal(
+        renter_before - ALT_RENT_FEE
+      );
+    });
+  });
+
+  describe("Deposit tiers", async () => {
+    const tiers = [
+      {
+        minCompletedRentals: new anchor.BN(1),
+        maxDisputesLost: new anchor.BN(0),
+        discountBps: 5000,
+      },
+    ];
+    let tenant: any;
+
+    before(async () => {
+      //one clean rental gives the renter a profile that reaches the tier
+      tenant = await new_renter();
+      const first = await list_new_car();
+      await rent_listed_car(first, tenant);
+      await return_car(first, tenant);
+    });
+
+    it("Checking a tier discounting more than the whole deposit is rejected", async () => {
+      await expect_error(
+        list_new_car({ depositTiers: [{ ...tiers[0], discountBps: 10_001 }] }),
+        "InvalidDepositTier"
+      );
+    });
+
+    it("Checking a renter with a qualifying track record pays the discounted deposit", async () => {
+      const car = await list_new_car({ depositTiers: tiers });
+      await rent_listed_car(car, tenant, { renterProfile: tenant.profile });
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.escrowedDeposit.toNumber()).to.equal(
+        DEPOSIT_FEE.toNumber() / 2
+      );
+      expect(await balance(car.rent_vault)).to.equal(
+        RENT_FEE.toNumber() + DEPOSIT_FEE.toNumber() / 2
+      );
+    });
+  });
+
+  describe("Attested renters", async () => {
+    const CLAIM_LICENCE_VALID = 1 << 0;
+    let issuer: anchor.web3.Keypair;
+    let attestation_issuer: anchor.web3.PublicKey;
+    let car: any;
+    let tenant: any;
+    let attestation: anchor.web3.PublicKey;
+
+    const attest = (expires_at: number) =>
+      program.methods
+        .writeAttestation(CLAIM_LICENCE_VALID, new anchor.BN(expires_at))
+        .accountsStrict({
+          issuer: issuer.publicKey,
+          attestationIssuer: attestation_issuer,
+          subject: tenant.key,
+          attestation,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([issuer])
+        .rpc();
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    before(async () => {
+      issuer = anchor.web3.Keypair.generate();
+      await transfer_sol(1, issuer.publicKey, paySigner);
+
+      attestation_issuer = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("issuer"), issuer.publicKey.toBuffer()],
+        program.programId
+      )[0];
+      const proposal = await create_proposal({
+        setAttestationIssuer: { issuer: issuer.publicKey, active: true },
+      });
+      await program.methods
+        .setAttestationIssuer()
+        .accountsStrict({
+          payer: provider.wallet.publicKey,
+          multisig,
+          proposal,
+          config,
+          issuer: issuer.publicKey,
+          attestationIssuer: attestation_issuer,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .rpc();
+
+      car = await list_new_car({ requiredClaims: CLAIM_LICENCE_VALID });
+      tenant = await new_renter();
+      attestation = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("attestation"),
+          issuer.publicKey.toBuffer(),
+          tenant.key.toBuffer(),
+        ],
+        program.programId
+      )[0];
+    });
+
+    it("Checking a renter without an attestation is rejected", async () => {
+      await expect_error(rent_listed_car(car, tenant), "MissingAttestation");
+    });
+
+    it("Checking an expired attestation is rejected", async () => {
+      await attest(now() - 60);
+      await expect_error(
+        rent_listed_car(car, tenant, {
+          attestationIssuer: attestation_issuer,
+          attestation,
+        }),
+        "AttestationExpired"
+      );
+    });
+
+    it("Checking a renter with a current attestation can rent", async () => {
+      await attest(now() + 24 * 60 * 60);
+      await rent_listed_car(car, tenant, {
+        attestationIssuer: attestation_issuer,
+        attestation,
+      });
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.renter.toString()).to.equal(tenant.key.toString());
+    });
+  });
+
+  describe("Renter lists", async () => {
+    let allowlist: anchor.web3.PublicKey;
+    let blocklist: anchor.web3.PublicKey;
+    let allowed: any;
+    let blocked: any;
+
+    const owner_key = () => new anchor.web3.PublicKey(owner.publicKey);
+
+    const create_list = async (list_id: number, kind: any, renter: any) => {
+      const list = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("renter_list"),
+          owner_key().toBuffer(),
+          new anchor.BN(list_id).toArrayLike(Buffer, "le", 2),
+        ],
+        program.programId
+      )[0];
+      const owner_signer = convert_keypair_to_anchor_compatiable(owner);
+
+      await program.methods
+        .createRenterList(list_id, kind)
+        .accountsStrict({
+          owner: owner_key(),
+          renterList: list,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([owner_signer])
+        .rpc();
+      await program.methods
+        .updateRenterList([renter.key], [])
+        .accountsStrict({ owner: owner_key(), renterList: list })
+        .signers([owner_signer])
+        .rpc();
+
+      return list;
+    };
+
+    before(async () => {
+      allowed = await new_renter();
+      blocked = await new_renter();
+      allowlist = await create_list(0, { allow: {} }, allowed);
+      blocklist = await create_list(1, { block: {} }, blocked);
+    });
+
+    it("Checking a renter missing from the allowlist is rejected", async () => {
+      const car = await list_new_car({ allowlist });
+      await expect_error(
+        rent_listed_car(car, blocked, { allowlist }),
+        "RenterNotAllowed"
+      );
+    });
+
+    it("Checking an allowlisted renter can rent", async () => {
+      const car = await list_new_car({ allowlist });
+      await rent_listed_car(car, allowed, { allowlist });
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.renter.toString()).to.equal(allowed.key.toString());
+    });
+
+    it("Checking a blocklisted renter is rejected", async () => {
+      const car = await list_new_car({ blocklist });
+      await expect_error(
+        rent_listed_car(car, blocked, { blocklist }),
+        "RenterBlocked"
+      );
+    });
+
+    it("Checking a renter missing from the blocklist can rent", async () => {
+      const car = await list_new_car({ blocklist });
+      await rent_listed_car(car, allowed, { blocklist });
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.renter.toString()).to.equal(allowed.key.toString());
+    });
+  });
+
+  describe("Operators", async () => {
+    const OPERATOR_UPDATE_PRICES = 1 << 1;
+    let operator: anchor.web3.Keypair;
+    let operator_grant: anchor.web3.PublicKey;
+    let car: any;
+
+    const owner_key = () => new anchor.web3.PublicKey(owner.publicKey);
+
+    const reprice = (authority: anchor.web3.Keypair, grant: any, rent_fee: number) =>
+      program.methods
+        .updateListingPrice(new anchor.BN(rent_fee), DEPOSIT_FEE)
+        .accountsStrict({
+          authority: authority.publicKey,
+          owner: owner_key(),
+          operatorGrant: grant,
+          rentalState: car.rental_state,
+          rentFeeMint: null,
+        })
+        .signers([authority])
+        .rpc();
+
+    before(async () => {
+      operator = anchor.web3.Keypair.generate();
+      await transfer_sol(1, operator.publicKey, paySigner);
+
+      operator_grant = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("operator"),
+          owner_key().toBuffer(),
+          operator.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+      await program.methods
+        .setOperator(OPERATOR_UPDATE_PRICES)
+        .accountsStrict({
+          owner: owner_key(),
+          operator: operator.publicKey,
+          operatorGrant: operator_grant,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+      car = await list_new_car();
+    });
+
+    it("Checking a key without a grant cannot act for the owner", async () => {
+      await expect_error(
+        reprice(anchor.web3.Keypair.generate(), null, RENT_FEE.toNumber() + 1),
+        "OperatorNotAuthorized"
+      );
+    });
+
+    it("Checking an operator can use the permission it was granted", async () => {
+      await reprice(operator, operator_grant, RENT_FEE.toNumber() + 1);
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.rentFee.toNumber()).to.equal(RENT_FEE.toNumber() + 1);
+    });
+
+    it("Checking an operator cannot confirm returns without that permission", async () => {
+      const tenant = await new_renter();
+      await rent_listed_car(car, tenant);
+
+      await expect_error(
+        return_car(
+          car,
+          tenant,
+          { authority: operator.publicKey, operatorGrant: operator_grant },
+          [],
+          [],
+          [operator]
+        ),
+        "OperatorNotAuthorized"
+      );
+    });
+  });
+
+  describe("Payee splits", async () => {
+    let car: any;
+    let tenant: any;
+    let payees: anchor.web3.PublicKey[];
+    let payee_atas: anchor.web3.PublicKey[];
+
+    before(async () => {
+      payees = [
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate().publicKey,
+      ];
+      payee_atas = await Promise.all(
+        payees.map((payee) =>
+          token_account(new anchor.web3.PublicKey(rent_fee_mint), payee)
+        )
+      );
+      tenant = await new_renter();
+    });
+
+    it("Checking shares that do not add up to the whole are rejected", async () => {
+      await expect_error(
+        list_new_car({
+          payees: [
+            { wallet: payees[0], shareBps: 6000 },
+            { wallet: payees[1], shareBps: 3000 },
+          ],
+        }),
+        "InvalidPayees"
+      );
+    });
+
+    it("Checking payee accounts passed out of order are rejected", async () => {
+      car = await list_new_car({
+        payees: [
+          { wallet: payees[0], shareBps: 6000 },
+          { wallet: payees[1], shareBps: 4000 },
+        ],
+      });
+      await rent_listed_car(car, tenant);
+
+      await expect_error(
+        return_car(car, tenant, {}, [payee_atas[1], payee_atas[0]]),
+        "InvalidPayeeAccount"
+      );
+    });
+
+    it("Checking the rent is split across the payees, the last taking the rounding", async () => {
+      const owner_before = await balance(car.owner_fee_ata);
+
+      await return_car(car, tenant, {}, payee_atas);
+
+      expect(await balance(payee_atas[0])).to.equal(3);
+      expect(await balance(payee_atas[1])).to.equal(2);
+      expect(await balance(car.owner_fee_ata)).to.equal(owner_before);
+    });
+  });
+
+  describe("Overdue claims", async () => {
+    let car: any;
+    let tenant: any;
+
+    // the renter doesn't sign, the claim is for when they stop responding
+    const claim_overdue = () =>
+      program.methods
+        .claimOverdue()
+        .accountsStrict({
+          owner: new anchor.web3.PublicKey(owner.publicKey),
+          authority: new anchor.web3.PublicKey(owner.publicKey),
+          operatorGrant: null,
+          renter: tenant.key,
+          collectionMint: new anchor.web3.PublicKey(collection_mint.publicKey),
+          fleet: null,
+          carNftMint: car.mint,
+          rentFeeMint: car.pay_mint,
+          rentalState: car.rental_state,
+          rentVault: car.rent_vault,
+          solVault: null,
+          vault: car.vault,
+          metadata: car.metadata,
+          masterEdition: car.master_edition,
+          ownerTokenRecord: null,
+          vaultTokenRecord: null,
+          authorizationRules: null,
+          authorizationRulesProgram: null,
+          sysvarInstructions: null,
+          ownerAta: car.owner_ata,
+          ownerFeeAta: car.owner_fee_ata,
+          ownerProfile: owner_profile,
+          renterProfile: tenant.profile,
+          referrerStats: null,
+          banEntry: tenant.ban_entry,
+          rentalPassMint: car.rental_pass_mint,
+          renterPass: getAssociatedTokenAddressSync(
+            car.rental_pass_mint,
+            tenant.key,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          systemProgram: SYSTEM_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: car.token_program,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+          metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([convert_keypair_to_anchor_compatiable(owner)])
+        .rpc();
+
+    before(async () => {
+      car = await list_new_car();
+      tenant = await new_renter();
+      await rent_listed_car(car, tenant);
+    });
+
+    // the grace period is days long, so only the early claim can be exercised against a live cluster
+    it("Checking a rental cannot be claimed before the grace period runs out", async () => {
+      await expect_error(claim_overdue(), "RentalNotOverdue");
+
+      const state = await program.account.rentalState.fetch(car.rental_state);
+      expect(state.rented).to.equal(true);
+      expect(await connection.getAccountInfo(tenant.ban_entry)).to.equal(null);
+    });
+  });
+
+  describe("Referrals", async () => {
+    const REFERRAL_FEE_BPS = 2000;
+    let referrer: any;
+    let referrer_stats: anchor.web3.PublicKey;
+    let car: any;
+
+    // registers `wallet` as a referrer, unapproved until the multisig says otherwise
+    const register = async (wallet: any) => {
+      const stats = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("referrer"), wallet.key.toBuffer()],
+        program.programId
+      )[0];
+
+      await program.methods
+        .registerReferrer()
+        .accountsStrict({
+          referrer: wallet.key,
+          referrerStats: stats,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .signers([wallet.keypair])
+        .rpc();
+
+      return stats;
+    };
+
+    const approve = async (wallet: any, stats: anchor.web3.PublicKey) => {
+      const proposal = await create_proposal({
+        approveReferrer: { referrer: wallet.key, approved: true },
+      });
+
+      await program.methods
+        .approveReferrer()
+        .accountsStrict({
+          multisig,
+          proposal,
+          config,
+          referrerStats: stats,
+        })
+        .rpc();
+    };
+
+    before(async () => {
+      referrer = await new_renter();
+      referrer_stats = await register(referrer);
+      car = await list_new_car({ referralFeeBps: REFERRAL_FEE_BPS });
+    });
+
+    it("Checking a referrer the multisig hasn't approved cannot be named", async () => {
+      await expect_error(
+        rent_listed_car(car, await new_renter(), { referrerStats: referrer_stats }),
+        "ReferrerNotApproved"
+      );
+    });
+
+    it("Checking a renter cannot refer themselves", async () => {
+      await approve(referrer, referrer_stats);
+
+      await expect_error(
+        rent_listed_car(car, referrer, { referrerStats: referrer_stats }),
+        "SelfReferral"
+      );
+    });
+
+    it("Checking the referrer is paid the listing's share of the rent", async () => {
+      const tenant = await new_renter();
+      await rent_listed_car(car, tenant, { referrerStats: referrer_stats });
+
+      const referrer_before = await balance(referrer.fee_ata);
+      await return_car(car, tenant, {
+        referrerStats: referrer_stats,
+        referrerFeeAta: referrer.fee_ata,
+        referrer: referrer.key,
+      });
+
+      const referral_fee = Math.floor(
+        (RENT_FEE.toNumber() * REFERRAL_FEE_BPS) / 10000
+      );
+      expect(await balance(referrer.fee_ata)).to.equal(
+        referrer_before + referral_fee
+      );
+
+      const stats = await program.account.referrerStats.fetch(referrer_stats);
+      expect(stats.rentalsSettled.toNumber()).to.equal(1);
+      expect(stats.totalEarned.toNumber()).to.equal(referral_fee);
+    });
+  });
+
+  describe("Multisig threshold", async () => {
+    let second_member: anchor.web3.Keypair;
+    let restore: anchor.web3.PublicKey;
+
+    const update_multisig = (proposal: anchor.web3.PublicKey) =>
+      program.methods
+        .updateMultisig()
+        .accountsStrict({
+          multisig,
+          proposal,
+        })
+        .rpc();
+
+    const approve_proposal = (
+      proposal: anchor.web3.PublicKey,
+      member: anchor.web3.PublicKey,
+      signers: anchor.web3.Keypair[] = []
+    ) =>
+      program.methods
+        .approveProposal()
+        .accountsStrict({
+          member,
+          multisig,
+          proposal,
+        })
+        .signers(signers)
+        .rpc();
+
+    before(async () => {
+      second_member = anchor.web3.Keypair.generate();
+
+      await update_multisig(
+        await create_proposal({
+          updateMultisig: {
+            members: [provider.wallet.publicKey, second_member.publicKey],
+            threshold: 2,
+          },
+        })
+      );
+
+      //puts the test wallet back in sole control once the second member signs off
+      restore = await create_proposal({
+        updateMultisig: { members: [provider.wallet.publicKey], threshold: 1 },
+      });
+    });
+
+    it("Checking a proposal cannot be executed below the threshold", async () => {
+      await expect_error(update_multisig(restore), "ThresholdNotMet");
+    });
+
+    it("Checking a member cannot approve the same proposal twice", async () => {
+      await expect_error(
+        approve_proposal(restore, provider.wallet.publicKey),
+        "AlreadyApproved"
+      );
+    });
+
+    it("Checking a proposal executes once enough members approve", async () => {
+      await approve_proposal(restore, second_member.publicKey, [second_member]);
+      await update_multisig(restore);
+
+      const multisig_data = await program.account.multisig.fetch(multisig);
+      expect(multisig_data.threshold).to.equal(1);
+      expect(multisig_data.members.length).to.equal(1);
+    });
+  });
+
+  describe("Rental state migration", async () => {
+    // unversioned listing loaded by the local validator, see tests/fixtures/rental_state_v0.json.
+    // listed and unrented, at the old RENT_FEE and DEPOSIT_FEE
+    const V0_RENTAL_STATE = new anchor.web3.PublicKey(
+      "C7JbRKH9aSa8duMVJstn34FpnWferxeP1MxNU3AmMGnF"
+    );
+
+    const migrate = () =>
+      program.methods
+        .migrateRentalState()
+        .accountsStrict({
+          payer: provider.wallet.publicKey,
+          rentalState: V0_RENTAL_STATE,
+          systemProgram: SYSTEM_PROGRAM_ID,
+        })
+        .rpc();
+
+    before(async function () {
+      if (!(await connection.getAccountInfo(V0_RENTAL_STATE))) {
+        this.skip();
+      }
+    });
+
+    it("Checking an unversioned listing migrates to the current layout", async () => {
+      await migrate();
+
+      const state = await program.account.rentalState.fetch(V0_RENTAL_STATE);
+      expect(state.version).to.equal(1);
+      expect(state.rentFee.toNumber()).to.equal(RENT_FEE.toNumber());
+      expect(state.depositAmount.toNumber()).to.equal(DEPOSIT_FEE.toNumber());
+      expect(state.listed).to.equal(true);
+      expect(state.rented).to.equal(false);
+      //no mint was on record before the upgrade, the owner pins one before it can be rented
+      expect(state.rentFeeMint).to.equal(null);
+    });
+
+    it("Checking a migrated listing cannot be migrated again", async () => {
+      await expect_error(migrate(), "AlreadyMigrated");
+    });
+  });
 });